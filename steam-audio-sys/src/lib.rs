@@ -54,6 +54,12 @@ impl From<&[f32; 3]> for ffi::IPLVector3 {
     }
 }
 
+impl From<ffi::IPLVector3> for [f32; 3] {
+    fn from(vec: ffi::IPLVector3) -> Self {
+        [vec.x, vec.y, vec.z]
+    }
+}
+
 impl From<bool> for ffi::IPLbool {
     fn from(b: bool) -> Self {
         match b {
@@ -4,9 +4,10 @@ use steam_audio::{
     Orientation,
 };
 
+use glam::Vec3;
 use std::{error::Error, path::Path};
 
-const FILENAME: &'static str = "assets/Secret Lie.ogg";
+const FILENAME: &str = "assets/Secret Lie.ogg";
 
 fn file_stem<P: AsRef<Path>>(p: P) -> String {
     p.as_ref()
@@ -17,14 +18,16 @@ fn file_stem<P: AsRef<Path>>(p: P) -> String {
 }
 
 fn raw_to_file(
+    context: &Context,
     kind: &'static str,
     name: String,
     data: Vec<Vec<f32>>,
 ) -> Result<(), Box<dyn Error>> {
     let out_name = format!("assets/out/{}/{}.raw", kind, name);
     println!("outputting to `{}`", out_name);
-    let interleaved = steam_audio::interleave(data);
-    steam_audio::write_file(out_name, interleaved)
+    let interleaved = steam_audio::interleave(context, &data)?;
+    steam_audio::write_file(out_name, interleaved)?;
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -34,11 +37,10 @@ fn main() -> Result<(), Box<dyn Error>> {
     let hrtf = HRTF::new(&context, &audio_settings, &hrtf_settings)?;
 
     let audio = steam_audio::read_ogg(FILENAME)?;
-    let audio_buffer = AudioBuffer::from_raw_pcm(&audio_settings, vec![audio]);
 
     let mut simulation_settings = SimulationSettings::from_audio_settings(&audio_settings);
     simulation_settings.flags = SimulationFlags::all();
-    let mut simulator = Simulator::new(&mut context, &simulation_settings)?;
+    let mut simulator = Simulator::new(&context, &simulation_settings)?;
 
     let scene_settings = SceneSettings::default();
     let mut scene = Scene::new(&mut context, &scene_settings)?;
@@ -64,7 +66,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     simulator.commit();
 
     let source_settings = &SourceSettings::default();
-    let source = Source::new(&simulator, &source_settings)?;
+    let source = Source::new(&simulator, source_settings)?;
 
     let listener = [0.0; 3];
 
@@ -80,37 +82,46 @@ fn main() -> Result<(), Box<dyn Error>> {
     );
 
     simulator.add_source(&source);
-    dbg!();
     simulator.commit();
-    dbg!();
-    dbg!();
 
     let outputs = source.get_outputs(SimulationFlags::all());
     dbg!(outputs);
 
+    let frame_size = audio_settings.frame_size() as usize;
     let mut output: Vec<Vec<f32>> = vec![vec![]; 2];
     let mut binaural_output: Vec<Vec<f32>> = vec![vec![]; 2];
-    let mut binaural_output_buffer = AudioBuffer::frame_buffer_with_channels(&audio_settings, 2);
-    let mut direct_output_buffer = AudioBuffer::frame_buffer_with_channels(&audio_settings, 2);
-    let frame_length = audio_buffer.frames();
+    let mut input = vec![0.0; frame_size];
+    let mut binaural_frame = [vec![0.0; frame_size], vec![0.0; frame_size]];
+    let mut direct_frame = [vec![0.0; frame_size], vec![0.0; frame_size]];
+    let frame_length = audio.len().div_ceil(frame_size);
 
     let direct_effect = DirectEffect::new(&context, &audio_settings, 2)?;
     let binaural_effect = BinauralEffect::new(&context, &audio_settings, &hrtf)?;
-    for (frame_index, frame) in audio_buffer.into_iter().enumerate() {
+    for (frame_index, chunk) in audio.chunks(frame_size).enumerate() {
+        input[..chunk.len()].copy_from_slice(chunk);
+        input[chunk.len()..].fill(0.0);
+
         let time = (frame_index as f32 / frame_length as f32) * std::f32::consts::TAU * 5.0;
 
         let position = Vec3::new(time.cos() * 2.0, 0.0, time.sin() * 2.0);
 
-        let direction = (position - listener).normalize();
+        let listener_orientation = Orientation {
+            origin: listener,
+            ..Default::default()
+        };
 
-        let mut params = BinauralParams::default();
-        params.interpolation = HRTFInterpolation::Bilinear;
-        params.direction = direction.into();
+        let params = BinauralParams {
+            interpolation: HRTFInterpolation::Bilinear,
+            ..BinauralParams::from_positions(&context, &listener_orientation, position)
+        };
 
-        binaural_effect.apply_to_buffer(&params, frame, &mut binaural_output_buffer)?;
-        let (ptrs, binaural_frame) = binaural_output_buffer.current_frame();
+        let [left, right] = &mut binaural_frame;
+        binaural_effect.apply_to_slices(&params, &[&input], &mut [left, right])?;
 
-        steam_audio::extend_deinterleaved(&mut binaural_output, &binaural_output_buffer.data);
+        steam_audio::extend_deinterleaved(
+            &mut binaural_output,
+            binaural_frame.iter().map(|channel| &channel[..chunk.len()]),
+        );
 
         source.set_inputs(
             SimulationFlags::all(),
@@ -129,21 +140,22 @@ fn main() -> Result<(), Box<dyn Error>> {
         simulator.run_direct();
         let mut outputs = source.get_outputs(SimulationFlags::all());
         outputs.direct.flags = DirectEffectFlags::all();
-        //let direct = DirectEffectParams::default();
-        //dbg!(outputs.direct.distance_attenuation);
-        dbg!(outputs.direct.occlusion);
 
-        direct_effect.apply_to_buffer(
+        let [left, right] = &mut direct_frame;
+        direct_effect.apply_to_slices(
             &outputs.direct,
-            binaural_frame,
-            &mut direct_output_buffer,
+            &[&binaural_frame[0], &binaural_frame[1]],
+            &mut [left, right],
         )?;
-        steam_audio::extend_deinterleaved(&mut output, &direct_output_buffer.data);
+        steam_audio::extend_deinterleaved(
+            &mut output,
+            direct_frame.iter().map(|channel| &channel[..chunk.len()]),
+        );
     }
 
     let filestem = file_stem(FILENAME);
-    raw_to_file("simulation", filestem.clone(), output)?;
-    raw_to_file("binaural", filestem, binaural_output)?;
+    raw_to_file(&context, "simulation", filestem.clone(), output)?;
+    raw_to_file(&context, "binaural", filestem, binaural_output)?;
 
     Ok(())
 }
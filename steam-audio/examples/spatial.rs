@@ -0,0 +1,32 @@
+use steam_audio::prelude::*;
+
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+const FILENAME: &str = "assets/eduardo.ogg";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let context = Context::new(&ContextSettings::default())?;
+    let audio_settings = AudioSettings::default();
+    let hrtf = HRTF::new(&context, &audio_settings, &HRTFSettings::default())?;
+
+    let file = std::fs::File::open(FILENAME)?;
+    let decoder = rodio::Decoder::new(file)?;
+
+    let spatial = Spatial::resampled(&context, &audio_settings, &hrtf, decoder)?;
+    let handle = spatial.handle();
+
+    let (_stream, stream_handle) = rodio::OutputStream::try_default()?;
+    let sink = rodio::Sink::try_new(&stream_handle)?;
+    sink.append(spatial);
+
+    // Circle the source around the listener until it finishes playing.
+    let start = Instant::now();
+    while !sink.empty() {
+        let time = start.elapsed().as_secs_f32();
+        handle.set_source_position([time.cos() * 2.0, 0.0, time.sin() * 2.0]);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    Ok(())
+}
@@ -6,13 +6,13 @@ use steam_audio::prelude::*;
 use std::error::Error;
 use std::path::Path;
 
-const FILENAME: &'static str = "assets/eduardo.ogg";
+const FILENAME: &str = "assets/eduardo.ogg";
 
 fn binaural_effect(
     context: &Context,
     audio_settings: &AudioSettings,
     hrtf: &HRTF,
-    mut decoder: rodio::Decoder<std::fs::File>,
+    mut decoder: Resampler<rodio::Decoder<std::fs::File>>,
 ) -> Result<(), Box<dyn Error>> {
     let mut input_buffer = DeinterleavedFrame::new(
         audio_settings.frame_size() as usize,
@@ -20,8 +20,6 @@ fn binaural_effect(
         audio_settings.sampling_rate(),
     );
 
-    input_buffer.push_source(&mut decoder);
-
     let mut output: Vec<Vec<f32>> = vec![vec![]; 2];
     let mut output_buffer = DeinterleavedFrame::new(
         audio_settings.frame_size() as usize,
@@ -32,22 +30,35 @@ fn binaural_effect(
     let mut frame_index = 0;
     let frame_length = input_buffer.frame_size();
 
-    let binaural_effect = BinauralEffect::new(&context, &audio_settings, &hrtf)?;
-    while input_buffer.push_source(&mut decoder) {
+    let binaural_effect = BinauralEffect::new(context, audio_settings, hrtf)?;
+    loop {
+        let valid_samples = match input_buffer.push_source(&mut decoder)? {
+            Filled::Full => frame_length,
+            Filled::Partial(valid_samples) => valid_samples,
+            Filled::Empty => break,
+        };
+
         let time = (frame_index as f32 / frame_length as f32) * std::f32::consts::TAU * 15.0;
 
-        let mut params = BinauralParams::default();
-        params.interpolation = HRTFInterpolation::Bilinear;
-        params.direction = [time.cos(), 0.0, time.sin()];
+        let params = BinauralParams {
+            interpolation: HRTFInterpolation::Bilinear,
+            direction: [time.cos(), 0.0, time.sin()],
+            ..Default::default()
+        };
 
         binaural_effect.apply_to_buffer(&params, &mut input_buffer, &mut output_buffer)?;
 
-        steam_audio::extend_deinterleaved(&mut output, &output_buffer.current_frame);
+        steam_audio::extend_deinterleaved(
+            &mut output,
+            output_buffer
+                .iter_channels()
+                .map(|channel| &channel[..valid_samples]),
+        );
         frame_index += 1;
     }
 
     let filestem = file_stem(FILENAME);
-    raw_to_file("binaural", filestem, output)?;
+    raw_to_file(context, "binaural", filestem, output)?;
 
     Ok(())
 }
@@ -61,7 +72,7 @@ fn ambisonics_effect(
 ) -> Result<(), Box<dyn Error>> {
     let encoded = ambisonics_encode_effect(context, audio_settings, hrtf, input_buffer)?;
     let output = ambisonics_decode_effect(context, audio_settings, hrtf, encoded)?;
-    raw_to_file("ambisonics", file_stem(FILENAME), output.data)
+    raw_to_file(context, "ambisonics", file_stem(FILENAME), output.data)
 }
 
 fn ambisonics_encode_effect(
@@ -125,14 +136,16 @@ fn file_stem<P: AsRef<Path>>(p: P) -> String {
 }
 
 fn raw_to_file(
+    context: &Context,
     kind: &'static str,
     name: String,
     data: Vec<Vec<f32>>,
 ) -> Result<(), Box<dyn Error>> {
     let out_name = format!("assets/out/{}/{}.raw", kind, name);
     println!("outputting to `{}`", out_name);
-    let interleaved = steam_audio::interleave(data);
-    steam_audio::write_file(out_name, interleaved)
+    let interleaved = steam_audio::interleave(context, &data)?;
+    steam_audio::write_file(out_name, interleaved)?;
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -142,7 +155,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let hrtf = HRTF::new(&context, &audio_settings, &hrtf_settings)?;
 
     let file = std::fs::File::open(FILENAME)?;
-    let source = rodio::Decoder::new(file)?;
+    let source = Resampler::for_audio_settings(rodio::Decoder::new(file)?, &audio_settings)?;
     binaural_effect(&context, &audio_settings, &hrtf, source)?;
     //ambisonics_effect(&context, &audio_settings, &hrtf, audio_buffer.clone())?;
 
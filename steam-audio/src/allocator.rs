@@ -0,0 +1,229 @@
+use std::alloc::{GlobalAlloc, Layout};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+use steam_audio_sys::ffi;
+
+/// Forwards to Rust's global allocator, whatever `#[global_allocator]` is set to.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RustGlobal;
+
+unsafe impl GlobalAlloc for RustGlobal {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        std::alloc::alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        std::alloc::dealloc(ptr, layout)
+    }
+}
+
+/// Allocator steam audio gets its memory from, e.g. a [`GlobalAlloc`] arena
+/// reserved for audio.
+#[derive(Copy, Clone)]
+pub struct Allocator(&'static (dyn GlobalAlloc + Sync));
+
+impl Allocator {
+    pub fn new(allocator: &'static (dyn GlobalAlloc + Sync)) -> Self {
+        Self(allocator)
+    }
+}
+
+impl Default for Allocator {
+    fn default() -> Self {
+        Self(&RustGlobal)
+    }
+}
+
+impl std::fmt::Debug for Allocator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Allocator").finish_non_exhaustive()
+    }
+}
+
+/// Memory steam audio has allocated through [`alloc_callback`].
+///
+/// The counters are process-global, every context and every [`Allocator`]
+/// adds to the same numbers.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct AllocationStats {
+    /// Bytes currently allocated, not counting the bookkeeping header.
+    pub live_bytes: usize,
+    /// Highest `live_bytes` since startup or [`reset_peak`].
+    pub peak_bytes: usize,
+    /// Allocations currently alive.
+    pub live_allocations: usize,
+    /// Allocations made since startup.
+    pub allocations: usize,
+}
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+static LIVE_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Current process-global allocation counters, see [`AllocationStats`].
+pub fn stats() -> AllocationStats {
+    AllocationStats {
+        live_bytes: LIVE_BYTES.load(Ordering::Relaxed),
+        peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+        live_allocations: LIVE_ALLOCATIONS.load(Ordering::Relaxed),
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+    }
+}
+
+/// Start measuring the peak again from the bytes currently allocated.
+///
+/// Affects the peak seen by every context, the counters are process-global.
+pub fn reset_peak() {
+    PEAK_BYTES.store(LIVE_BYTES.load(Ordering::Relaxed), Ordering::Relaxed);
+}
+
+// Like the log callback, the allocation callbacks have no user data pointer.
+// Every block remembers the allocator it came from so switching is safe.
+static ALLOCATOR: RwLock<Allocator> = RwLock::new(Allocator(&RustGlobal));
+
+pub(crate) fn install(allocator: Allocator) {
+    *ALLOCATOR
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = allocator;
+}
+
+// Stored right in front of every block handed to steam audio, steam audio's
+// free callback only gives us the pointer back.
+struct Header {
+    size: usize,
+    align: usize,
+    allocator: Allocator,
+}
+
+// Where the block starts relative to the start of the allocation, the header
+// sits in the bytes just before it.
+fn block_layout(size: usize, align: usize) -> Option<(Layout, usize)> {
+    let align = align.max(std::mem::align_of::<Header>());
+    let offset = std::mem::size_of::<Header>().checked_add(align - 1)? & !(align - 1);
+    let layout = Layout::from_size_align(offset.checked_add(size)?, align).ok()?;
+    Some((layout, offset))
+}
+
+pub(crate) unsafe extern "C" fn alloc_callback(
+    size: ffi::IPLsize,
+    alignment: ffi::IPLsize,
+) -> *mut c_void {
+    let allocator = *ALLOCATOR
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    allocate(allocator, size, alignment)
+}
+
+unsafe fn allocate(allocator: Allocator, size: usize, align: usize) -> *mut c_void {
+    let align = align.max(1);
+    if !align.is_power_of_two() {
+        return std::ptr::null_mut();
+    }
+
+    let (layout, offset) = match block_layout(size, align) {
+        Some(block) => block,
+        None => return std::ptr::null_mut(),
+    };
+
+    let base = allocator.0.alloc(layout);
+    if base.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let block = base.add(offset);
+    (block as *mut Header).sub(1).write(Header {
+        size,
+        align,
+        allocator,
+    });
+
+    let live = LIVE_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+    LIVE_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+
+    block as *mut c_void
+}
+
+pub(crate) unsafe extern "C" fn free_callback(block: *mut c_void) {
+    if block.is_null() {
+        return;
+    }
+
+    let block = block as *mut u8;
+    let Header {
+        size,
+        align,
+        allocator,
+    } = (block as *mut Header).sub(1).read();
+
+    // Same computation as when allocating, so this can't fail for a header we
+    // wrote. Leaking a corrupted block beats panicking into steam audio.
+    let Some((layout, offset)) = block_layout(size, align) else {
+        return;
+    };
+    allocator.0.dealloc(block.sub(offset), layout);
+
+    LIVE_BYTES.fetch_sub(size, Ordering::Relaxed);
+    LIVE_ALLOCATIONS.fetch_sub(1, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counts its own blocks, the global stats are shared with every other
+    // test that creates a context.
+    struct Counting {
+        blocks: AtomicUsize,
+        bytes: AtomicUsize,
+    }
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            self.blocks.fetch_add(1, Ordering::Relaxed);
+            self.bytes.fetch_add(layout.size(), Ordering::Relaxed);
+            std::alloc::alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            self.blocks.fetch_sub(1, Ordering::Relaxed);
+            self.bytes.fetch_sub(layout.size(), Ordering::Relaxed);
+            std::alloc::dealloc(ptr, layout)
+        }
+    }
+
+    static COUNTING: Counting = Counting {
+        blocks: AtomicUsize::new(0),
+        bytes: AtomicUsize::new(0),
+    };
+
+    #[test]
+    fn blocks_are_aligned_and_freed_by_their_allocator() {
+        // Bypasses the installed allocator so other tests can't swap it out from under us.
+        let blocks: Vec<_> = [(24, 8), (100, 64), (3, 1), (0, 16)]
+            .iter()
+            .map(|&(size, align)| {
+                let block = unsafe { allocate(Allocator::new(&COUNTING), size, align) };
+                assert!(!block.is_null());
+                assert_eq!(block as usize % align, 0);
+                unsafe { std::ptr::write_bytes(block as *mut u8, 0xff, size) };
+                block
+            })
+            .collect();
+
+        assert_eq!(COUNTING.blocks.load(Ordering::Relaxed), 4);
+        assert!(COUNTING.bytes.load(Ordering::Relaxed) >= 127);
+        assert!(unsafe { allocate(Allocator::new(&COUNTING), 8, 3) }.is_null());
+
+        // Blocks go back to the allocator they came from, whatever is installed now.
+        for block in blocks {
+            unsafe { free_callback(block) };
+        }
+        assert_eq!(COUNTING.blocks.load(Ordering::Relaxed), 0);
+        assert_eq!(COUNTING.bytes.load(Ordering::Relaxed), 0);
+    }
+}
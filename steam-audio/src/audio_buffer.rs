@@ -1,9 +1,36 @@
 use std::fmt;
+use std::marker::PhantomData;
 
+use steam_audio_sys::ffi;
+
+use crate::effect::ambisonics::AmbisonicsType;
+use crate::prelude::*;
+
+/// Maximum number of channels that can be handed to steam audio at once.
+///
+/// Enough for 7th order ambisonics.
+pub const MAX_CHANNELS: usize = 64;
+
+/// How much of a [`DeinterleavedFrame`] was filled by [`DeinterleavedFrame::push_source`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Filled {
+    /// Every sample in the frame came from the source.
+    Full,
+    /// Only the first `n` samples of each channel are valid, the rest are zeroed.
+    Partial(usize),
+    /// The source had nothing left, the frame is silent.
+    Empty,
+}
+
+/// A single frame of deinterleaved audio.
+///
+/// All channels live in one contiguous allocation, channel after channel, so
+/// cloning is cheap to reason about and processing never has to allocate.
 #[derive(Clone)]
 pub struct DeinterleavedFrame {
-    pub current_frame: Vec<Vec<f32>>,
-    ptrs: Vec<*mut f32>,
+    data: Vec<f32>,
+    frame_size: usize,
+    channels: u16,
     sample_rate: u32,
     channel_offset: u16,
     frame_offset: usize,
@@ -11,7 +38,6 @@ pub struct DeinterleavedFrame {
 
 impl fmt::Debug for DeinterleavedFrame {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use rodio::Source;
         formatter
             .debug_struct("DeinterleavedFrame")
             .field("channels", &self.channels())
@@ -23,79 +49,320 @@ impl fmt::Debug for DeinterleavedFrame {
 
 impl DeinterleavedFrame {
     pub fn new(frame_size: usize, channels: u16, sample_rate: u32) -> Self {
-        let mut frame = Self {
-            current_frame: vec![vec![0.0; frame_size]; channels as usize],
-            ptrs: Vec::new(),
-            sample_rate: sample_rate,
+        Self {
+            data: vec![0.0; frame_size * channels as usize],
+            frame_size,
+            channels,
+            sample_rate,
             channel_offset: 0,
             frame_offset: 0,
-        };
-
-        frame.ptrs = frame
-            .current_frame
-            .iter()
-            .map(|d| d.as_ptr() as *mut _)
-            .collect();
-        frame
+        }
     }
 
-    pub fn from_source<S, I>(frame_size: usize, source: &mut S) -> Self
+    pub fn from_source<S, I>(frame_size: usize, source: &mut S) -> Result<Self, SteamAudioError>
     where
         I: rodio::Sample,
         S: rodio::Source + Iterator<Item = I>,
     {
         let mut frame = Self::new(frame_size, source.channels(), source.sample_rate());
-        frame.push_source(source);
-        frame
+        frame.push_source(source)?;
+        Ok(frame)
     }
 
-    pub fn push_source<S, I>(&mut self, source: &mut S) -> bool
+    /// Fill this frame with the next `frame_size` samples per channel from `source`.
+    ///
+    /// If the source runs dry mid-frame the rest of the frame is zeroed, so the
+    /// tail never holds samples from the previous frame. Nothing is consumed if
+    /// the source isn't at the frame's sample rate.
+    ///
+    /// Filling also stops early where the source's channels or sample rate
+    /// change, as announced by [`rodio::Source::current_frame_len`], so a frame
+    /// never mixes two layouts.
+    pub fn push_source<S, I>(&mut self, source: &mut S) -> Result<Filled, SteamAudioError>
     where
         I: rodio::Sample,
         S: rodio::Source + Iterator<Item = I>,
     {
-        let mut channel = 0;
-        let mut frame = 0;
+        self.push_source_span(source).map(|(filled, _)| filled)
+    }
 
-        while let Some(sample) = source.next() {
-            self.current_frame[channel][frame] = sample.to_f32();
+    /// Same as [`DeinterleavedFrame::push_source`], also returning whether
+    /// filling stopped because the source's layout changed.
+    pub(crate) fn push_source_span<S, I>(
+        &mut self,
+        source: &mut S,
+    ) -> Result<(Filled, bool), SteamAudioError>
+    where
+        I: rodio::Sample,
+        S: rodio::Source + Iterator<Item = I>,
+    {
+        if source.sample_rate() != self.sample_rate {
+            return Err(SteamAudioError::SampleRateMismatch {
+                expected: self.sample_rate,
+                got: source.sample_rate(),
+            });
+        }
 
-            if channel as u16 >= self.channels() - 1 {
-                channel = 0;
+        let channels = self.channels() as usize;
+        let frame_size = self.frame_size();
+        let mut filled = 0;
+        let mut layout_changed = false;
+        // Interleaved samples until the source may change layout.
+        let mut span = source.current_frame_len();
 
-                if frame >= self.frame_size() - 1 {
-                    return true;
+        'frames: while filled < frame_size {
+            if span == Some(0) {
+                if source.channels() as usize != channels
+                    || source.sample_rate() != self.sample_rate
+                {
+                    layout_changed = true;
+                    break;
                 }
+                span = source.current_frame_len();
+            }
 
-                frame += 1;
-            } else {
-                channel += 1;
+            for channel in 0..channels {
+                match source.next() {
+                    Some(sample) => self.channel_mut(channel)[filled] = sample.to_f32(),
+                    None => {
+                        // Keep what we got of the last sample, silencing the missing channels.
+                        if channel > 0 {
+                            for channel in channel..channels {
+                                self.channel_mut(channel)[filled] = 0.0;
+                            }
+                            filled += 1;
+                        }
+
+                        break 'frames;
+                    }
+                }
             }
+
+            span = span.map(|span| span.saturating_sub(channels));
+            filled += 1;
+        }
+
+        for channel in self.iter_channels_mut() {
+            channel[filled..].fill(0.0);
         }
 
-        false
+        self.rewind();
+
+        let filled = match filled {
+            0 => Filled::Empty,
+            filled if filled == frame_size => Filled::Full,
+            filled => Filled::Partial(filled),
+        };
+        Ok((filled, layout_changed))
+    }
+
+    /// Change the number of channels, keeping the frame size and sample rate.
+    ///
+    /// Only allocates if the frame never held `channels` channels before, so
+    /// create it with the most channels it will need to switch layouts on the
+    /// audio thread. New channels start out silent.
+    pub fn set_channels(&mut self, channels: u16) {
+        self.data.resize(self.frame_size * channels as usize, 0.0);
+        self.channels = channels;
+        self.rewind();
     }
 
     pub fn frame_size(&self) -> usize {
-        self.current_frame.get(0).map(|d| d.len()).unwrap_or(0)
+        self.frame_size
     }
 
     pub fn channels(&self) -> u16 {
-        self.current_frame.len() as u16
+        self.channels
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channel(&self, index: usize) -> &[f32] {
+        &self.data[index * self.frame_size..(index + 1) * self.frame_size]
+    }
+
+    pub fn channel_mut(&mut self, index: usize) -> &mut [f32] {
+        &mut self.data[index * self.frame_size..(index + 1) * self.frame_size]
     }
 
-    pub unsafe fn ptrs(&mut self) -> *mut *mut f32 {
-        self.ptrs.as_mut_ptr()
+    pub fn iter_channels(&self) -> impl Iterator<Item = &[f32]> + '_ {
+        // `chunks_exact` doesn't accept 0, an empty frame has no channels to yield anyway.
+        self.data.chunks_exact(self.frame_size.max(1))
+    }
+
+    pub fn iter_channels_mut(&mut self) -> impl Iterator<Item = &mut [f32]> + '_ {
+        self.data.chunks_exact_mut(self.frame_size.max(1))
+    }
+
+    /// Start iterating over the frame from the first sample again.
+    pub fn rewind(&mut self) {
+        self.channel_offset = 0;
+        self.frame_offset = 0;
+    }
+
+    /// Interleaved samples left to iterate over.
+    fn remaining(&self) -> usize {
+        if self.channels == 0 {
+            return 0;
+        }
+
+        let total = self.frame_size * self.channels as usize;
+        let read = self.frame_offset * self.channels as usize + self.channel_offset as usize;
+        total.saturating_sub(read)
+    }
+
+    /// Silence every channel.
+    pub fn clear(&mut self) {
+        self.data.fill(0.0);
+    }
+
+    /// Deinterleave `interleaved` into a new frame, `interleaved.len()` must be a
+    /// multiple of `channels`.
+    pub fn from_interleaved(
+        context: &Context,
+        interleaved: &[f32],
+        channels: u16,
+        sample_rate: u32,
+    ) -> Result<Self, SteamAudioError> {
+        let frame_size = interleaved.len() / (channels as usize).max(1);
+        SteamAudioError::check_frame_size(frame_size * channels as usize, interleaved.len())?;
+
+        let mut frame = Self::new(frame_size, channels, sample_rate);
+        frame.fill_from_interleaved(context, interleaved)?;
+        Ok(frame)
+    }
+
+    /// Deinterleave `interleaved` into this frame.
+    ///
+    /// 123123123 -> 111222333
+    pub fn fill_from_interleaved(
+        &mut self,
+        context: &Context,
+        interleaved: &[f32],
+    ) -> Result<(), SteamAudioError> {
+        SteamAudioError::check_frame_size(
+            self.channels() as usize * self.frame_size(),
+            interleaved.len(),
+        )?;
+
+        let mut buffer = RawBuffer::from_frame(self)?;
+        unsafe {
+            ffi::iplAudioBufferDeinterleave(
+                context.inner_raw(),
+                interleaved.as_ptr() as *mut f32,
+                &mut buffer.as_ffi(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Interleave this frame into `output`.
+    ///
+    /// 111222333 -> 123123123
+    pub fn to_interleaved(
+        &self,
+        context: &Context,
+        output: &mut [f32],
+    ) -> Result<(), SteamAudioError> {
+        SteamAudioError::check_frame_size(
+            self.channels() as usize * self.frame_size(),
+            output.len(),
+        )?;
+
+        let mut buffer = RawBuffer::from_frame_ref(self)?;
+        unsafe {
+            ffi::iplAudioBufferInterleave(
+                context.inner_raw(),
+                &mut buffer.as_ffi(),
+                output.as_mut_ptr(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Add this frame on top of `mix`, both frames must have the same channels and frame size.
+    pub fn mix_into(
+        &self,
+        context: &Context,
+        mix: &mut DeinterleavedFrame,
+    ) -> Result<(), SteamAudioError> {
+        let mut input = RawBuffer::from_frame_ref(self)?;
+        let mut output = RawBuffer::from_frame(mix)?;
+        output.check_channels(input.channels() as usize)?;
+        input.check_frame_size(&output)?;
+
+        unsafe {
+            ffi::iplAudioBufferMix(
+                context.inner_raw(),
+                &mut input.as_ffi(),
+                &mut output.as_ffi(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Average all channels of this frame into the mono `output` frame.
+    pub fn downmix_to_mono(
+        &self,
+        context: &Context,
+        output: &mut DeinterleavedFrame,
+    ) -> Result<(), SteamAudioError> {
+        let mut input = RawBuffer::from_frame_ref(self)?;
+        let mut output = RawBuffer::from_frame(output)?;
+        output.check_channels(1)?;
+        input.check_frame_size(&output)?;
+
+        unsafe {
+            ffi::iplAudioBufferDownmix(
+                context.inner_raw(),
+                &mut input.as_ffi(),
+                &mut output.as_ffi(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Convert the ambisonic data in this frame from one format to another, in-place.
+    pub fn convert_ambisonics(
+        &mut self,
+        context: &Context,
+        from: AmbisonicsType,
+        to: AmbisonicsType,
+    ) -> Result<(), SteamAudioError> {
+        let mut raw = RawBuffer::from_frame(self)?;
+        let mut buffer = raw.as_ffi();
+        let buffer_pointer = &mut buffer as *mut _;
+        unsafe {
+            ffi::iplAudioBufferConvertAmbisonics(
+                context.inner_raw(),
+                from.into(),
+                to.into(),
+                buffer_pointer,
+                buffer_pointer,
+            );
+        }
+
+        Ok(())
     }
 }
 
+/// Plays the frame back once, interleaved.
+///
+/// Refilling the frame with [`DeinterleavedFrame::push_source`] or calling
+/// [`DeinterleavedFrame::rewind`] starts playback from the beginning again.
 impl rodio::Source for DeinterleavedFrame {
     fn current_frame_len(&self) -> Option<usize> {
-        Some(self.frame_size())
+        Some(self.remaining())
     }
 
     fn channels(&self) -> u16 {
-        self.current_frame.len() as u16
+        self.channels
     }
 
     fn sample_rate(&self) -> u32 {
@@ -103,27 +370,215 @@ impl rodio::Source for DeinterleavedFrame {
     }
 
     fn total_duration(&self) -> Option<std::time::Duration> {
-        None
+        Some(std::time::Duration::from_secs_f64(
+            self.frame_size as f64 / self.sample_rate as f64,
+        ))
     }
 }
 
 impl Iterator for DeinterleavedFrame {
     type Item = f32;
     fn next(&mut self) -> Option<Self::Item> {
-        let sample = self
-            .current_frame
-            .get(self.channel_offset as usize)
-            .map(|channel| channel.get(self.frame_offset))
-            .flatten()
-            .cloned();
-
-        if self.channels() == self.channel_offset {
+        if self.channels == 0 || self.frame_offset >= self.frame_size {
+            return None;
+        }
+
+        let sample = self.channel(self.channel_offset as usize)[self.frame_offset];
+
+        self.channel_offset += 1;
+        if self.channel_offset == self.channels {
             self.channel_offset = 0;
             self.frame_offset += 1;
-        } else {
-            self.channel_offset += 1;
         }
 
-        sample
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for DeinterleavedFrame {}
+
+/// Channel pointer table for handing audio to steam audio without allocating.
+///
+/// Borrows the samples it points to for `'a`, so the pointers can't dangle.
+pub(crate) struct RawBuffer<'a> {
+    ptrs: [*mut f32; MAX_CHANNELS],
+    channels: u16,
+    frame_size: usize,
+    _marker: PhantomData<&'a mut [f32]>,
+}
+
+impl<'a> RawBuffer<'a> {
+    pub(crate) fn from_frame(frame: &'a mut DeinterleavedFrame) -> Result<Self, SteamAudioError> {
+        let frame_size = frame.frame_size();
+        let mut buffer = Self::empty(frame.channels() as usize, frame_size)?;
+        for (ptr, channel) in buffer.ptrs.iter_mut().zip(frame.iter_channels_mut()) {
+            *ptr = channel.as_mut_ptr();
+        }
+        Ok(buffer)
+    }
+
+    /// Steam audio only reads from input buffers even though it takes them mutably.
+    pub(crate) fn from_frame_ref(frame: &'a DeinterleavedFrame) -> Result<Self, SteamAudioError> {
+        let frame_size = frame.frame_size();
+        let mut buffer = Self::empty(frame.channels() as usize, frame_size)?;
+        for (ptr, channel) in buffer.ptrs.iter_mut().zip(frame.iter_channels()) {
+            *ptr = channel.as_ptr() as *mut f32;
+        }
+        Ok(buffer)
+    }
+
+    /// Steam audio only reads from input buffers even though it takes them mutably.
+    pub(crate) fn from_slices(channels: &'a [&[f32]]) -> Result<Self, SteamAudioError> {
+        let frame_size = Self::slices_frame_size(channels.iter().map(|channel| channel.len()))?;
+        let mut buffer = Self::empty(channels.len(), frame_size)?;
+        for (ptr, channel) in buffer.ptrs.iter_mut().zip(channels.iter()) {
+            *ptr = channel.as_ptr() as *mut f32;
+        }
+        Ok(buffer)
+    }
+
+    pub(crate) fn from_slices_mut(channels: &'a mut [&mut [f32]]) -> Result<Self, SteamAudioError> {
+        let frame_size = Self::slices_frame_size(channels.iter().map(|channel| channel.len()))?;
+        let mut buffer = Self::empty(channels.len(), frame_size)?;
+        for (ptr, channel) in buffer.ptrs.iter_mut().zip(channels.iter_mut()) {
+            *ptr = channel.as_mut_ptr();
+        }
+        Ok(buffer)
+    }
+
+    // Every channel has to be the same length.
+    fn slices_frame_size(
+        mut lengths: impl ExactSizeIterator<Item = usize>,
+    ) -> Result<usize, SteamAudioError> {
+        let frame_size = lengths.next().unwrap_or(0);
+        for length in lengths {
+            SteamAudioError::check_frame_size(frame_size, length)?;
+        }
+        Ok(frame_size)
+    }
+
+    // There can't be more than `MAX_CHANNELS` channels.
+    fn empty(channels: usize, frame_size: usize) -> Result<Self, SteamAudioError> {
+        if channels > MAX_CHANNELS {
+            return Err(SteamAudioError::ChannelMismatch {
+                expected: MAX_CHANNELS,
+                got: channels,
+            });
+        }
+
+        Ok(Self {
+            ptrs: [std::ptr::null_mut(); MAX_CHANNELS],
+            channels: channels as u16,
+            frame_size,
+            _marker: PhantomData,
+        })
+    }
+
+    pub(crate) fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub(crate) fn check_channels(&self, expected: usize) -> Result<(), SteamAudioError> {
+        SteamAudioError::check_channels(expected, self.channels as usize)
+    }
+
+    pub(crate) fn check_frame_size(&self, other: &RawBuffer) -> Result<(), SteamAudioError> {
+        SteamAudioError::check_frame_size(self.frame_size, other.frame_size)
+    }
+
+    /// Steam audio assumes every buffer is exactly as long as the frame size an
+    /// effect was created with, anything else reads or writes out of bounds.
+    pub(crate) fn check_num_samples(&self, expected: usize) -> Result<(), SteamAudioError> {
+        SteamAudioError::check_frame_size(expected, self.frame_size)
+    }
+
+    /// The returned buffer points into `self`, it must not outlive it.
+    pub(crate) fn as_ffi(&mut self) -> ffi::IPLAudioBuffer {
+        ffi::IPLAudioBuffer {
+            numChannels: self.channels as i32,
+            numSamples: self.frame_size as i32,
+            data: self.ptrs.as_mut_ptr(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_with(channels: &[Vec<f32>]) -> DeinterleavedFrame {
+        let mut frame = DeinterleavedFrame::new(channels[0].len(), channels.len() as u16, 48000);
+        for (channel, samples) in frame.iter_channels_mut().zip(channels) {
+            channel.copy_from_slice(samples);
+        }
+        frame
+    }
+
+    #[test]
+    fn iterates_interleaved() {
+        let channels = vec![
+            vec![1.0, 2.0, 3.0, 4.0],
+            vec![10.0, 20.0, 30.0, 40.0],
+            vec![100.0, 200.0, 300.0, 400.0],
+        ];
+        let mut frame = frame_with(&channels);
+
+        assert_eq!(frame.len(), 12);
+        let samples: Vec<f32> = frame.by_ref().collect();
+        assert_eq!(
+            samples,
+            [1.0, 10.0, 100.0, 2.0, 20.0, 200.0, 3.0, 30.0, 300.0, 4.0, 40.0, 400.0]
+        );
+        assert_eq!(frame.next(), None);
+
+        frame.rewind();
+        assert_eq!(frame.next(), Some(1.0));
+        assert_eq!(frame.next(), Some(10.0));
+    }
+
+    #[test]
+    fn push_source_pads_partial_frame() {
+        let mut frame = frame_with(&[vec![9.0; 4], vec![9.0; 4]]);
+
+        let mut source = rodio::buffer::SamplesBuffer::new(2, 48000, vec![1.0f32, 2.0, 3.0]);
+        assert_eq!(frame.push_source(&mut source).unwrap(), Filled::Partial(2));
+        assert_eq!(frame.channel(0), &[1.0, 3.0, 0.0, 0.0]);
+        assert_eq!(frame.channel(1), &[2.0, 0.0, 0.0, 0.0]);
+
+        assert_eq!(frame.push_source(&mut source).unwrap(), Filled::Empty);
+        assert!(frame.iter_channels().flatten().all(|sample| *sample == 0.0));
+    }
+
+    #[test]
+    fn push_source_rejects_other_sample_rates() {
+        let mut frame = frame_with(&[vec![9.0; 4], vec![9.0; 4]]);
+
+        let mut source = rodio::buffer::SamplesBuffer::new(2, 44100, vec![1.0f32; 8]);
+        assert!(matches!(
+            frame.push_source(&mut source),
+            Err(SteamAudioError::SampleRateMismatch {
+                expected: 48000,
+                got: 44100
+            })
+        ));
+        assert_eq!(frame.channel(0), &[9.0; 4]);
+        assert_eq!(source.count(), 8);
+    }
+
+    #[test]
+    fn too_many_channels_is_an_error() {
+        let mut frame = DeinterleavedFrame::new(4, MAX_CHANNELS as u16 + 1, 48000);
+        assert!(matches!(
+            RawBuffer::from_frame(&mut frame),
+            Err(SteamAudioError::ChannelMismatch { expected: MAX_CHANNELS, got }) if got == MAX_CHANNELS + 1
+        ));
+
+        let mut frame = DeinterleavedFrame::new(4, MAX_CHANNELS as u16, 48000);
+        assert!(RawBuffer::from_frame(&mut frame).is_ok());
     }
 }
@@ -1,52 +1,251 @@
-use std::ffi::{c_void, CStr};
-
 use steam_audio_sys::ffi;
 
 use crate::prelude::*;
 
-#[derive(Debug, Default)]
+/// An instruction set steam audio can run with, what a [`SimdLevel`] resolves to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ConcreteLevel {
+    SSE2,
+    SSE4,
+    AVX,
+    AVX2,
+    AVX512,
+    NEON,
+}
+
+impl ConcreteLevel {
+    /// Best level supported by this CPU.
+    pub fn detect() -> ConcreteLevel {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("avx512f") {
+                ConcreteLevel::AVX512
+            } else if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+                ConcreteLevel::AVX2
+            } else if is_x86_feature_detected!("avx") {
+                ConcreteLevel::AVX
+            } else if is_x86_feature_detected!("sse4.1") {
+                ConcreteLevel::SSE4
+            } else {
+                ConcreteLevel::SSE2
+            }
+        }
+
+        #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+        {
+            ConcreteLevel::NEON
+        }
+
+        #[cfg(not(any(
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "arm",
+            target_arch = "aarch64"
+        )))]
+        {
+            ConcreteLevel::SSE2
+        }
+    }
+
+    fn x86_rank(self) -> Option<u8> {
+        match self {
+            Self::SSE2 => Some(0),
+            Self::SSE4 => Some(1),
+            Self::AVX => Some(2),
+            Self::AVX2 => Some(3),
+            Self::AVX512 => Some(4),
+            Self::NEON => None,
+        }
+    }
+}
+
+impl From<ConcreteLevel> for ffi::IPLSIMDLevel {
+    fn from(concrete_level: ConcreteLevel) -> Self {
+        match concrete_level {
+            ConcreteLevel::SSE2 => ffi::IPLSIMDLevel::IPL_SIMDLEVEL_SSE2,
+            ConcreteLevel::SSE4 => ffi::IPLSIMDLevel::IPL_SIMDLEVEL_SSE4,
+            ConcreteLevel::AVX => ffi::IPLSIMDLevel::IPL_SIMDLEVEL_AVX,
+            ConcreteLevel::AVX2 => ffi::IPLSIMDLevel::IPL_SIMDLEVEL_AVX2,
+            ConcreteLevel::AVX512 => ffi::IPLSIMDLevel::IPL_SIMDLEVEL_AVX512,
+            ConcreteLevel::NEON => ffi::IPLSIMDLevel::IPL_SIMDLEVEL_NEON,
+        }
+    }
+}
+
+/// Instruction set steam audio is allowed to use.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SimdLevel {
+    SSE2,
+    SSE4,
+    AVX,
+    AVX2,
+    AVX512,
+    NEON,
+    /// Best level supported by the CPU we are running on.
+    #[default]
+    Auto,
+    /// Like [`SimdLevel::Auto`] but never above the given level, e.g. to avoid
+    /// the clock throttling some CPUs do under AVX512.
+    Max(ConcreteLevel),
+}
+
+impl SimdLevel {
+    /// Best level supported by this CPU.
+    pub fn detect() -> SimdLevel {
+        ConcreteLevel::detect().into()
+    }
+
+    /// The concrete level this resolves to, running detection for
+    /// [`SimdLevel::Auto`] and [`SimdLevel::Max`].
+    pub fn resolve(self) -> ConcreteLevel {
+        match self {
+            Self::SSE2 => ConcreteLevel::SSE2,
+            Self::SSE4 => ConcreteLevel::SSE4,
+            Self::AVX => ConcreteLevel::AVX,
+            Self::AVX2 => ConcreteLevel::AVX2,
+            Self::AVX512 => ConcreteLevel::AVX512,
+            Self::NEON => ConcreteLevel::NEON,
+            Self::Auto => ConcreteLevel::detect(),
+            Self::Max(max) => {
+                let detected = ConcreteLevel::detect();
+                match (detected.x86_rank(), max.x86_rank()) {
+                    (Some(detected_rank), Some(max_rank)) if max_rank < detected_rank => max,
+                    _ => detected,
+                }
+            }
+        }
+    }
+}
+
+impl From<ConcreteLevel> for SimdLevel {
+    fn from(level: ConcreteLevel) -> Self {
+        match level {
+            ConcreteLevel::SSE2 => Self::SSE2,
+            ConcreteLevel::SSE4 => Self::SSE4,
+            ConcreteLevel::AVX => Self::AVX,
+            ConcreteLevel::AVX2 => Self::AVX2,
+            ConcreteLevel::AVX512 => Self::AVX512,
+            ConcreteLevel::NEON => Self::NEON,
+        }
+    }
+}
+
+impl From<SimdLevel> for ffi::IPLSIMDLevel {
+    fn from(simd_level: SimdLevel) -> Self {
+        simd_level.resolve().into()
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct ContextSettings {
     version: Option<u32>,
-    simd_level: Option<ffi::IPLSIMDLevel>,
+    simd_level: SimdLevel,
+    log_sink: Option<LogSink>,
+    log_level: Option<LogLevel>,
+    allocator: Allocator,
 }
 
-unsafe extern "C" fn log_callback(level: ffi::IPLLogLevel, message: *const ::std::os::raw::c_char) {
-    dbg!();
-    let c_str: &CStr = CStr::from_ptr(message);
-    let str = c_str.to_str().unwrap();
-    eprintln!("{:?}: {}", level, str);
-}
+impl ContextSettings {
+    pub fn builder() -> ContextSettingsBuilder {
+        ContextSettingsBuilder {
+            settings: Self::default(),
+        }
+    }
+
+    /// Steam audio API version requested, defaults to the version of the bindings.
+    pub fn version(&self) -> u32 {
+        self.version.unwrap_or(ffi::STEAMAUDIO_VERSION)
+    }
+
+    pub fn simd_level(&self) -> SimdLevel {
+        self.simd_level
+    }
+
+    /// Shared by every context, the last context created with a sink wins.
+    /// Contexts without one keep the sink already in place.
+    pub fn log_sink(&self) -> Option<&LogSink> {
+        self.log_sink.as_ref()
+    }
 
-unsafe extern "C" fn alloc_callback(size: ffi::IPLsize, alignment: ffi::IPLsize) -> *mut c_void {
-    let layout = match std::alloc::Layout::from_size_align(size as usize, alignment as usize) {
-        Ok(layout) => layout,
-        _ => return std::ptr::null_mut(),
-    };
+    /// Messages below this level are dropped.
+    ///
+    /// Shared like [`ContextSettings::log_sink`], only set if the builder was given a level.
+    pub fn log_level(&self) -> LogLevel {
+        self.log_level.unwrap_or_default()
+    }
 
-    std::alloc::alloc(layout) as *mut c_void
+    pub fn allocator(&self) -> Allocator {
+        self.allocator
+    }
 }
 
-/*
-unsafe extern "C" fn free_callback(size: ffi::IPLsize, alignment: ffi::IPLsize) -> *mut c_void {
-    let layout = match std::alloc::Layout::from_size_align(size as usize, alignment as usize) {
-        Ok(layout) => layout,
-        _ => return std::ptr::null_mut(),
-    };
+/// Validated construction of [`ContextSettings`], starting from the defaults.
+#[derive(Debug, Clone)]
+pub struct ContextSettingsBuilder {
+    settings: ContextSettings,
+}
+
+impl ContextSettingsBuilder {
+    /// Version packed as `major << 16 | minor << 8 | patch`, like `STEAMAUDIO_VERSION`.
+    pub fn version(mut self, version: u32) -> Self {
+        self.settings.version = Some(version);
+        self
+    }
+
+    pub fn simd_level(mut self, simd_level: SimdLevel) -> Self {
+        self.settings.simd_level = simd_level;
+        self
+    }
+
+    /// Send steam audio's log messages to `sink` instead of the `log`/`tracing` backends.
+    ///
+    /// Steam audio only has a single log callback, so this replaces the sink of
+    /// any context created before.
+    pub fn log_sink<F>(mut self, sink: F) -> Self
+    where
+        F: Fn(LogLevel, &str) + Send + Sync + 'static,
+    {
+        self.settings.log_sink = Some(LogSink::new(sink));
+        self
+    }
 
-    std::alloc::dealloc(layout) as *mut c_void
+    pub fn log_level(mut self, log_level: LogLevel) -> Self {
+        self.settings.log_level = Some(log_level);
+        self
+    }
+
+    /// Allocate steam audio's memory from `allocator`, Rust's global allocator by default.
+    ///
+    /// Like the log sink this is shared by every context, memory already handed
+    /// out is still freed by the allocator it came from.
+    pub fn allocator(mut self, allocator: &'static (dyn std::alloc::GlobalAlloc + Sync)) -> Self {
+        self.settings.allocator = Allocator::new(allocator);
+        self
+    }
+
+    pub fn build(self) -> Result<ContextSettings, SteamAudioError> {
+        let requested = self.settings.version();
+        let linked = ffi::STEAMAUDIO_VERSION;
+
+        // Same rules as steam audio itself: matching major and no newer minor.
+        let major = |version: u32| version >> 16;
+        let minor = |version: u32| (version >> 8) & 0xff;
+        if major(requested) != major(linked) || minor(requested) > minor(linked) {
+            return Err(SteamAudioError::IncompatibleVersion { requested, linked });
+        }
+
+        Ok(self.settings)
+    }
 }
-*/
 
-impl Into<ffi::IPLContextSettings> for &ContextSettings {
-    fn into(self) -> ffi::IPLContextSettings {
+impl From<&ContextSettings> for ffi::IPLContextSettings {
+    fn from(context_settings: &ContextSettings) -> Self {
         ffi::IPLContextSettings {
-            version: self.version.unwrap_or(ffi::STEAMAUDIO_VERSION),
-            logCallback: Some(log_callback),
-            allocateCallback: Some(alloc_callback),
-            simdLevel: self
-                .simd_level
-                .unwrap_or(ffi::IPLSIMDLevel::IPL_SIMDLEVEL_SSE2),
-            freeCallback: None,
+            version: context_settings.version(),
+            logCallback: Some(crate::logging::log_callback),
+            allocateCallback: Some(crate::allocator::alloc_callback),
+            simdLevel: context_settings.simd_level.into(),
+            freeCallback: Some(crate::allocator::free_callback),
         }
     }
 }
@@ -57,6 +256,7 @@ pub struct Context {
     // are used after creation, but better to be safe here and to ensure
     // it is still alive while the context is alive.
     settings: ffi::IPLContextSettings,
+    simd_level: ConcreteLevel,
 }
 
 // This is supposedly safe as IPLContext is allowed to be used from multiple threads
@@ -77,10 +277,17 @@ impl crate::SteamAudioObject for Context {
 
 impl Context {
     pub fn new(settings: &ContextSettings) -> Result<Self, SteamAudioError> {
-        let ipl_settings: ffi::IPLContextSettings = settings.into();
+        crate::logging::install(settings.log_sink.clone(), settings.log_level);
+        crate::allocator::install(settings.allocator);
+
+        // Resolve once so the context reports exactly what steam audio was given.
+        let simd_level = settings.simd_level().resolve();
+        let mut ipl_settings: ffi::IPLContextSettings = settings.into();
+        ipl_settings.simdLevel = simd_level.into();
         let mut context = Self {
             inner: std::ptr::null_mut(),
             settings: ipl_settings,
+            simd_level,
         };
 
         unsafe {
@@ -99,9 +306,15 @@ impl Context {
             Context {
                 inner: new_context,
                 settings: self.settings,
+                simd_level: self.simd_level,
             }
         }
     }
+
+    /// The SIMD level the context was created with.
+    pub fn simd_level(&self) -> ConcreteLevel {
+        self.simd_level
+    }
 }
 
 impl Drop for Context {
@@ -111,3 +324,23 @@ impl Drop for Context {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_caps_the_detected_level() {
+        let detected = ConcreteLevel::detect();
+        assert_eq!(SimdLevel::Auto.resolve(), detected);
+        assert_eq!(SimdLevel::SSE4.resolve(), ConcreteLevel::SSE4);
+        assert_eq!(SimdLevel::Max(ConcreteLevel::AVX512).resolve(), detected);
+
+        let capped = SimdLevel::Max(ConcreteLevel::SSE2).resolve();
+        if detected == ConcreteLevel::NEON {
+            assert_eq!(capped, ConcreteLevel::NEON);
+        } else {
+            assert_eq!(capped, ConcreteLevel::SSE2);
+        }
+    }
+}
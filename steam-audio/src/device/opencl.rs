@@ -1,5 +1,7 @@
 use steam_audio_sys::ffi;
 
+// Not wrapped yet, nothing reads the handle.
+#[allow(dead_code)]
 pub struct OpenCLDevice(ffi::IPLOpenCLDevice);
 
 impl OpenCLDevice {}
@@ -0,0 +1,123 @@
+use steam_audio_sys::ffi;
+
+use crate::prelude::*;
+
+pub struct AmbisonicsBinauralParams {
+    pub order: u8,
+}
+
+impl Default for AmbisonicsBinauralParams {
+    fn default() -> Self {
+        Self { order: 1 }
+    }
+}
+
+impl AmbisonicsBinauralParams {
+    pub fn merge(&self, hrtf: ffi::IPLHRTF) -> ffi::IPLAmbisonicsBinauralEffectParams {
+        ffi::IPLAmbisonicsBinauralEffectParams {
+            hrtf,
+            order: self.order as i32,
+        }
+    }
+}
+
+/// Renders an ambisonic soundfield to headphones using the HRTF.
+///
+/// Unlike [`AmbisonicsDecode`] this does not rotate the soundfield, use
+/// [`AmbisonicsRotation`] first if the input is in world space.
+pub struct AmbisonicsBinaural {
+    inner: ffi::IPLAmbisonicsBinauralEffect,
+    audio_settings: AudioSettings,
+    // Retained, every apply hands it to steam audio again.
+    hrtf: HRTF,
+    max_order: u8,
+}
+
+unsafe impl Send for AmbisonicsBinaural {}
+unsafe impl Sync for AmbisonicsBinaural {}
+
+impl crate::SteamAudioObject for AmbisonicsBinaural {
+    type Object = ffi::IPLAmbisonicsBinauralEffect;
+    fn inner_raw(&self) -> Self::Object {
+        assert!(!self.inner.is_null());
+        self.inner
+    }
+    fn inner_mut(&mut self) -> *mut Self::Object {
+        std::ptr::addr_of_mut!(self.inner)
+    }
+}
+
+impl AmbisonicsBinaural {
+    pub fn new(
+        context: &Context,
+        audio_settings: &AudioSettings,
+        hrtf: &HRTF,
+        max_order: u8,
+    ) -> Result<Self, SteamAudioError> {
+        let mut effect = Self {
+            inner: std::ptr::null_mut(),
+            audio_settings: *audio_settings,
+            hrtf: hrtf.retain(),
+            max_order,
+        };
+
+        let mut effect_settings = ffi::IPLAmbisonicsBinauralEffectSettings {
+            hrtf: hrtf.inner_raw(),
+            maxOrder: max_order as i32,
+        };
+
+        unsafe {
+            match ffi::iplAmbisonicsBinauralEffectCreate(
+                context.inner_raw(),
+                &mut audio_settings.into(),
+                &mut effect_settings,
+                effect.inner_mut(),
+            ) {
+                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(effect),
+                err => Err(SteamAudioError::IPLError(err)),
+            }
+        }
+    }
+
+    pub fn max_order(&self) -> u8 {
+        self.max_order
+    }
+
+    pub fn reset(&self) {
+        unsafe {
+            ffi::iplAmbisonicsBinauralEffectReset(self.inner_raw());
+        }
+    }
+}
+
+impl crate::effect::private::RawEffect<AmbisonicsBinauralParams> for AmbisonicsBinaural {
+    fn audio_settings(&self) -> &AudioSettings {
+        &self.audio_settings
+    }
+
+    fn channels(
+        &self,
+        params: &AmbisonicsBinauralParams,
+    ) -> Result<(usize, usize), SteamAudioError> {
+        SteamAudioError::check_ambisonics_order(self.max_order, params.order)?;
+        Ok((crate::ambisonic_order_channels(params.order), 2))
+    }
+
+    unsafe fn apply_ffi(
+        &self,
+        params: &AmbisonicsBinauralParams,
+        input: &mut ffi::IPLAudioBuffer,
+        output: &mut ffi::IPLAudioBuffer,
+    ) {
+        let mut ipl_params = params.merge(self.hrtf.inner_raw());
+        ffi::iplAmbisonicsBinauralEffectApply(self.inner_raw(), &mut ipl_params, input, output);
+    }
+}
+
+impl Drop for AmbisonicsBinaural {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::iplAmbisonicsBinauralEffectRelease(self.inner_mut());
+        }
+    }
+}
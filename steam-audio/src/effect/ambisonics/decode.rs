@@ -1,45 +1,6 @@
-use glam::Vec3;
-use steam_audio_sys::ffi::{self, IPLAmbisonicsDecodeEffectParams};
-
-use crate::audio_buffer::{AudioBuffer, AudioBufferFrame};
-use crate::context::Context;
-use crate::error::SteamAudioError;
-use crate::hrtf::AudioSettings;
-use crate::prelude::HRTF;
-use crate::Orientation;
-
-// TODO
-#[derive(Debug, Clone)]
-pub enum SpeakerLayout {
-    Mono,
-    Stereo,
-    Quadraphonic,
-    Surround5_1,
-    Surround7_1,
-    Custom {
-        num_speakers: u32,
-        speakers: Vec<Vec3>,
-    },
-}
+use steam_audio_sys::ffi;
 
-impl Into<ffi::IPLSpeakerLayout> for SpeakerLayout {
-    fn into(self) -> ffi::IPLSpeakerLayout {
-        let kind = match self {
-            Self::Mono => ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_MONO,
-            Self::Stereo => ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_STEREO,
-            Self::Quadraphonic => ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_QUADRAPHONIC,
-            Self::Surround5_1 => ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_SURROUND_5_1,
-            Self::Surround7_1 => ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_SURROUND_7_1,
-            Self::Custom { .. } => unimplemented!(),
-        };
-
-        ffi::IPLSpeakerLayout {
-            type_: kind,
-            numSpeakers: 0,
-            speakers: std::ptr::null_mut(),
-        }
-    }
-}
+use crate::{prelude::*, Orientation};
 
 pub struct AmbisonicsDecodeSettings {
     pub speaker_layout: SpeakerLayout,
@@ -58,17 +19,17 @@ impl Default for AmbisonicsDecodeSettings {
 impl AmbisonicsDecodeSettings {
     pub fn merge(&self, hrtf: ffi::IPLHRTF) -> ffi::IPLAmbisonicsDecodeEffectSettings {
         ffi::IPLAmbisonicsDecodeEffectSettings {
-            hrtf: hrtf,
-            speakerLayout: self.speaker_layout.clone().into(),
+            hrtf,
+            speakerLayout: (&self.speaker_layout).into(),
             maxOrder: self.max_order as i32,
         }
     }
 }
 
 pub struct AmbisonicsDecodeParams {
-    order: u8,
-    orientation: Orientation,
-    binaural: bool,
+    pub order: u8,
+    pub orientation: Orientation,
+    pub binaural: bool,
 }
 
 impl Default for AmbisonicsDecodeParams {
@@ -87,14 +48,32 @@ impl AmbisonicsDecodeParams {
             order: self.order as i32,
             orientation: self.orientation.clone().into(),
             binaural: self.binaural.into(),
-            hrtf: hrtf,
+            hrtf,
         }
     }
 }
 
 pub struct AmbisonicsDecode {
     inner: ffi::IPLAmbisonicsDecodeEffect,
-    hrtf: ffi::IPLHRTF,
+    audio_settings: AudioSettings,
+    // Retained, every apply hands it to steam audio again.
+    hrtf: HRTF,
+    speaker_layout: SpeakerLayout,
+    max_order: u8,
+}
+
+unsafe impl Send for AmbisonicsDecode {}
+unsafe impl Sync for AmbisonicsDecode {}
+
+impl crate::SteamAudioObject for AmbisonicsDecode {
+    type Object = ffi::IPLAmbisonicsDecodeEffect;
+    fn inner_raw(&self) -> Self::Object {
+        assert!(!self.inner.is_null());
+        self.inner
+    }
+    fn inner_mut(&mut self) -> *mut Self::Object {
+        std::ptr::addr_of_mut!(self.inner)
+    }
 }
 
 impl AmbisonicsDecode {
@@ -104,73 +83,71 @@ impl AmbisonicsDecode {
         hrtf: &HRTF,
         decode_settings: &AmbisonicsDecodeSettings,
     ) -> Result<Self, SteamAudioError> {
-        let mut ambisonics_decode = Self {
-            inner: unsafe { std::mem::zeroed() },
-            hrtf: unsafe { hrtf.inner() },
+        let mut effect = Self {
+            inner: std::ptr::null_mut(),
+            audio_settings: *audio_settings,
+            hrtf: hrtf.retain(),
+            speaker_layout: decode_settings.speaker_layout.clone(),
+            max_order: decode_settings.max_order,
         };
 
-        let mut effect_settings = decode_settings.merge(unsafe { hrtf.inner() });
+        let mut effect_settings = decode_settings.merge(hrtf.inner_raw());
 
         unsafe {
             match ffi::iplAmbisonicsDecodeEffectCreate(
-                context.inner(),
+                context.inner_raw(),
                 &mut audio_settings.into(),
                 &mut effect_settings,
-                &mut ambisonics_decode.inner(),
+                effect.inner_mut(),
             ) {
-                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(ambisonics_decode),
+                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(effect),
                 err => Err(SteamAudioError::IPLError(err)),
             }
         }
     }
 
-    pub unsafe fn inner(&self) -> ffi::IPLAmbisonicsDecodeEffect {
-        self.inner
+    pub fn max_order(&self) -> u8 {
+        self.max_order
     }
 
-    pub fn apply_to_buffer(
-        &self,
-        params: &AmbisonicsDecodeParams,
-        mut frame: AudioBufferFrame,
-        output_buffer: &mut AudioBuffer,
-    ) -> Result<(), SteamAudioError> {
-        let mut output_ffi_buffer = unsafe { output_buffer.ffi_buffer_null() };
-        let mut data_ptrs = unsafe { output_buffer.data_ptrs() };
-        output_ffi_buffer.data = data_ptrs.as_mut_ptr();
-
-        let mut ipl_params: IPLAmbisonicsDecodeEffectParams = params.merge(self.hrtf);
-
-        unsafe {
-            let _effect_state = ffi::iplAmbisonicsDecodeEffectApply(
-                self.inner(),
-                &mut ipl_params,
-                &mut frame.0,
-                &mut output_ffi_buffer,
-            );
+    // Binaural rendering always outputs stereo, otherwise we output one channel per speaker.
+    fn output_channels(&self, params: &AmbisonicsDecodeParams) -> u16 {
+        if params.binaural {
+            2
+        } else {
+            self.speaker_layout.channels()
         }
+    }
+}
+
+impl crate::effect::private::RawEffect<AmbisonicsDecodeParams> for AmbisonicsDecode {
+    fn audio_settings(&self) -> &AudioSettings {
+        &self.audio_settings
+    }
 
-        Ok(())
+    fn channels(&self, params: &AmbisonicsDecodeParams) -> Result<(usize, usize), SteamAudioError> {
+        SteamAudioError::check_ambisonics_order(self.max_order, params.order)?;
+        Ok((
+            crate::ambisonic_order_channels(params.order),
+            self.output_channels(params) as usize,
+        ))
     }
 
-    pub fn apply(
+    unsafe fn apply_ffi(
         &self,
-        audio_settings: &AudioSettings,
         params: &AmbisonicsDecodeParams,
-        frame: AudioBufferFrame,
-    ) -> Result<AudioBuffer, SteamAudioError> {
-        let mut output_buffer = AudioBuffer::frame_buffer_with_channels(
-            audio_settings,
-            crate::ambisonic_order_channels(params.order),
-        );
-        self.apply_to_buffer(params, frame, &mut output_buffer)?;
-        Ok(output_buffer)
+        input: &mut ffi::IPLAudioBuffer,
+        output: &mut ffi::IPLAudioBuffer,
+    ) {
+        let mut ipl_params = params.merge(self.hrtf.inner_raw());
+        ffi::iplAmbisonicsDecodeEffectApply(self.inner_raw(), &mut ipl_params, input, output);
     }
 }
 
 impl Drop for AmbisonicsDecode {
     fn drop(&mut self) {
         unsafe {
-            ffi::iplAmbisonicsDecodeEffectRelease(&mut self.inner);
+            ffi::iplAmbisonicsDecodeEffectRelease(self.inner_mut());
         }
     }
 }
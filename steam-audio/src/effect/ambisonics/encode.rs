@@ -1,35 +1,49 @@
-use glam::Vec3;
-use steam_audio_sys::ffi::{self, IPLAmbisonicsEncodeEffectParams};
+use steam_audio_sys::ffi;
 
-use crate::audio_buffer::{AudioBuffer, AudioBufferFrame};
-use crate::context::Context;
-use crate::error::SteamAudioError;
-use crate::hrtf::AudioSettings;
+use crate::prelude::*;
 
 pub struct AmbisonicsEncodeParams {
-    pub direction: Vec3,
+    pub direction: [f32; 3],
     pub order: u8,
 }
 
 impl Default for AmbisonicsEncodeParams {
     fn default() -> Self {
         Self {
-            direction: glam::Vec3::ZERO,
+            direction: [0.0; 3],
             order: 1,
         }
     }
 }
 
-impl Into<ffi::IPLAmbisonicsEncodeEffectParams> for &AmbisonicsEncodeParams {
-    fn into(self) -> ffi::IPLAmbisonicsEncodeEffectParams {
+impl From<&AmbisonicsEncodeParams> for ffi::IPLAmbisonicsEncodeEffectParams {
+    fn from(ambisonics_encode_params: &AmbisonicsEncodeParams) -> Self {
         ffi::IPLAmbisonicsEncodeEffectParams {
-            direction: self.direction.into(),
-            order: self.order as i32,
+            direction: ambisonics_encode_params.direction.into(),
+            order: ambisonics_encode_params.order as i32,
         }
     }
 }
 
-pub struct AmbisonicsEncode(ffi::IPLAmbisonicsEncodeEffect);
+pub struct AmbisonicsEncode {
+    inner: ffi::IPLAmbisonicsEncodeEffect,
+    audio_settings: AudioSettings,
+    max_order: u8,
+}
+
+unsafe impl Send for AmbisonicsEncode {}
+unsafe impl Sync for AmbisonicsEncode {}
+
+impl crate::SteamAudioObject for AmbisonicsEncode {
+    type Object = ffi::IPLAmbisonicsEncodeEffect;
+    fn inner_raw(&self) -> Self::Object {
+        assert!(!self.inner.is_null());
+        self.inner
+    }
+    fn inner_mut(&mut self) -> *mut Self::Object {
+        std::ptr::addr_of_mut!(self.inner)
+    }
+}
 
 impl AmbisonicsEncode {
     pub fn new(
@@ -37,85 +51,59 @@ impl AmbisonicsEncode {
         audio_settings: &AudioSettings,
         max_order: u8,
     ) -> Result<Self, SteamAudioError> {
-        let mut ambisonics_encode = Self(unsafe { std::mem::zeroed() });
+        let mut effect = Self {
+            inner: std::ptr::null_mut(),
+            audio_settings: *audio_settings,
+            max_order,
+        };
+
         let mut effect_settings = ffi::IPLAmbisonicsEncodeEffectSettings {
             maxOrder: max_order as i32,
         };
 
         unsafe {
             match ffi::iplAmbisonicsEncodeEffectCreate(
-                context.inner(),
+                context.inner_raw(),
                 &mut audio_settings.into(),
                 &mut effect_settings,
-                &mut ambisonics_encode.0,
+                effect.inner_mut(),
             ) {
-                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(ambisonics_encode),
+                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(effect),
                 err => Err(SteamAudioError::IPLError(err)),
             }
         }
     }
 
-    pub unsafe fn inner(&self) -> ffi::IPLAmbisonicsEncodeEffect {
-        self.0
+    pub fn max_order(&self) -> u8 {
+        self.max_order
     }
+}
 
-    pub fn apply_to_buffer(
-        &self,
-        params: &AmbisonicsEncodeParams,
-        mut frame: AudioBufferFrame,
-        output_buffer: &mut AudioBuffer,
-    ) -> Result<(), SteamAudioError> {
-        assert_eq!(frame.channels(), 1);
-        assert_eq!(
-            output_buffer.channels(),
-            crate::ambisonic_order_channels(params.order)
-        );
-
-        let mut output_ffi_buffer = unsafe { output_buffer.ffi_buffer_null() };
-        let mut data_ptrs = unsafe { output_buffer.data_ptrs() };
-        output_ffi_buffer.data = data_ptrs.as_mut_ptr();
-
-        let mut ipl_params: IPLAmbisonicsEncodeEffectParams = params.into();
-
-        /*
-        dbg!(unsafe { frame.0.data });
-        dbg!(unsafe { *frame.0.data });
-        dbg!(unsafe { output_ffi_buffer.data });
-        dbg!(unsafe { *output_ffi_buffer.data });
-        dbg!();
-        */
-        unsafe {
-            let _effect_state = ffi::iplAmbisonicsEncodeEffectApply(
-                self.inner(),
-                &mut ipl_params,
-                &mut frame.0,
-                &mut output_ffi_buffer,
-            );
-            //dbg!(_effect_state);
-        }
+impl crate::effect::private::RawEffect<AmbisonicsEncodeParams> for AmbisonicsEncode {
+    fn audio_settings(&self) -> &AudioSettings {
+        &self.audio_settings
+    }
 
-        Ok(())
+    fn channels(&self, params: &AmbisonicsEncodeParams) -> Result<(usize, usize), SteamAudioError> {
+        SteamAudioError::check_ambisonics_order(self.max_order, params.order)?;
+        Ok((1, crate::ambisonic_order_channels(params.order)))
     }
 
-    pub fn apply(
+    unsafe fn apply_ffi(
         &self,
-        audio_settings: &AudioSettings,
         params: &AmbisonicsEncodeParams,
-        frame: AudioBufferFrame,
-    ) -> Result<AudioBuffer, SteamAudioError> {
-        let mut output_buffer = AudioBuffer::frame_buffer_with_channels(
-            audio_settings,
-            crate::ambisonic_order_channels(params.order),
-        );
-        self.apply_to_buffer(params, frame, &mut output_buffer)?;
-        Ok(output_buffer)
+        input: &mut ffi::IPLAudioBuffer,
+        output: &mut ffi::IPLAudioBuffer,
+    ) {
+        let mut ipl_params: ffi::IPLAmbisonicsEncodeEffectParams = params.into();
+        ffi::iplAmbisonicsEncodeEffectApply(self.inner_raw(), &mut ipl_params, input, output);
     }
 }
 
 impl Drop for AmbisonicsEncode {
     fn drop(&mut self) {
         unsafe {
-            ffi::iplAmbisonicsEncodeEffectRelease(&mut self.0);
+            ffi::iplAmbisonicsEncodeEffectRelease(self.inner_mut());
         }
     }
 }
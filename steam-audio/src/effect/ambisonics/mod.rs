@@ -1,5 +1,35 @@
+pub mod binaural;
 pub mod decode;
 pub mod encode;
+pub mod panning;
+pub mod rotation;
+
+use steam_audio_sys::ffi;
+
+/// Channel ordering and normalization of ambisonic audio.
+///
+/// N3D is what steam audio uses internally, convert to and from it when
+/// exchanging data with other audio engines.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum AmbisonicsType {
+    /// ACN channel ordering, orthonormal spherical harmonics.
+    #[default]
+    N3D,
+    /// ACN channel ordering, semi-normalized spherical harmonics (AmbiX).
+    SN3D,
+    /// Furse-Malham (B-format).
+    FuMa,
+}
+
+impl From<AmbisonicsType> for ffi::IPLAmbisonicsType {
+    fn from(ambisonics_type: AmbisonicsType) -> Self {
+        match ambisonics_type {
+            AmbisonicsType::N3D => ffi::IPLAmbisonicsType::IPL_AMBISONICSTYPE_N3D,
+            AmbisonicsType::SN3D => ffi::IPLAmbisonicsType::IPL_AMBISONICSTYPE_SN3D,
+            AmbisonicsType::FuMa => ffi::IPLAmbisonicsType::IPL_AMBISONICSTYPE_FUMA,
+        }
+    }
+}
 
 // How many channels the higher order ambisonic has.
 //
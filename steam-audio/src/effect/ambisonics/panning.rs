@@ -0,0 +1,147 @@
+use steam_audio_sys::ffi;
+
+use crate::prelude::*;
+
+pub struct AmbisonicsPanningSettings {
+    pub speaker_layout: SpeakerLayout,
+    pub max_order: u8,
+}
+
+impl Default for AmbisonicsPanningSettings {
+    fn default() -> Self {
+        Self {
+            speaker_layout: SpeakerLayout::Stereo,
+            max_order: 2,
+        }
+    }
+}
+
+impl From<&AmbisonicsPanningSettings> for ffi::IPLAmbisonicsPanningEffectSettings {
+    fn from(ambisonics_panning_settings: &AmbisonicsPanningSettings) -> Self {
+        ffi::IPLAmbisonicsPanningEffectSettings {
+            speakerLayout: (&ambisonics_panning_settings.speaker_layout).into(),
+            maxOrder: ambisonics_panning_settings.max_order as i32,
+        }
+    }
+}
+
+pub struct AmbisonicsPanningParams {
+    pub order: u8,
+}
+
+impl Default for AmbisonicsPanningParams {
+    fn default() -> Self {
+        Self { order: 1 }
+    }
+}
+
+impl From<&AmbisonicsPanningParams> for ffi::IPLAmbisonicsPanningEffectParams {
+    fn from(ambisonics_panning_params: &AmbisonicsPanningParams) -> Self {
+        ffi::IPLAmbisonicsPanningEffectParams {
+            order: ambisonics_panning_params.order as i32,
+        }
+    }
+}
+
+/// Renders an ambisonic soundfield to a speaker layout by panning.
+///
+/// Unlike [`AmbisonicsDecode`] this does not rotate the soundfield, use
+/// [`AmbisonicsRotation`] first if the input is in world space.
+pub struct AmbisonicsPanning {
+    inner: ffi::IPLAmbisonicsPanningEffect,
+    audio_settings: AudioSettings,
+    speaker_layout: SpeakerLayout,
+    max_order: u8,
+}
+
+unsafe impl Send for AmbisonicsPanning {}
+unsafe impl Sync for AmbisonicsPanning {}
+
+impl crate::SteamAudioObject for AmbisonicsPanning {
+    type Object = ffi::IPLAmbisonicsPanningEffect;
+    fn inner_raw(&self) -> Self::Object {
+        assert!(!self.inner.is_null());
+        self.inner
+    }
+    fn inner_mut(&mut self) -> *mut Self::Object {
+        std::ptr::addr_of_mut!(self.inner)
+    }
+}
+
+impl AmbisonicsPanning {
+    pub fn new(
+        context: &Context,
+        audio_settings: &AudioSettings,
+        panning_settings: &AmbisonicsPanningSettings,
+    ) -> Result<Self, SteamAudioError> {
+        let mut effect = Self {
+            inner: std::ptr::null_mut(),
+            audio_settings: *audio_settings,
+            speaker_layout: panning_settings.speaker_layout.clone(),
+            max_order: panning_settings.max_order,
+        };
+
+        let mut effect_settings: ffi::IPLAmbisonicsPanningEffectSettings = panning_settings.into();
+
+        unsafe {
+            match ffi::iplAmbisonicsPanningEffectCreate(
+                context.inner_raw(),
+                &mut audio_settings.into(),
+                &mut effect_settings,
+                effect.inner_mut(),
+            ) {
+                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(effect),
+                err => Err(SteamAudioError::IPLError(err)),
+            }
+        }
+    }
+
+    pub fn max_order(&self) -> u8 {
+        self.max_order
+    }
+
+    pub fn speaker_layout(&self) -> &SpeakerLayout {
+        &self.speaker_layout
+    }
+
+    pub fn reset(&self) {
+        unsafe {
+            ffi::iplAmbisonicsPanningEffectReset(self.inner_raw());
+        }
+    }
+}
+
+impl crate::effect::private::RawEffect<AmbisonicsPanningParams> for AmbisonicsPanning {
+    fn audio_settings(&self) -> &AudioSettings {
+        &self.audio_settings
+    }
+
+    fn channels(
+        &self,
+        params: &AmbisonicsPanningParams,
+    ) -> Result<(usize, usize), SteamAudioError> {
+        SteamAudioError::check_ambisonics_order(self.max_order, params.order)?;
+        Ok((
+            crate::ambisonic_order_channels(params.order),
+            self.speaker_layout.channels() as usize,
+        ))
+    }
+
+    unsafe fn apply_ffi(
+        &self,
+        params: &AmbisonicsPanningParams,
+        input: &mut ffi::IPLAudioBuffer,
+        output: &mut ffi::IPLAudioBuffer,
+    ) {
+        let mut ipl_params: ffi::IPLAmbisonicsPanningEffectParams = params.into();
+        ffi::iplAmbisonicsPanningEffectApply(self.inner_raw(), &mut ipl_params, input, output);
+    }
+}
+
+impl Drop for AmbisonicsPanning {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::iplAmbisonicsPanningEffectRelease(self.inner_mut());
+        }
+    }
+}
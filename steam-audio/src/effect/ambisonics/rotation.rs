@@ -0,0 +1,127 @@
+use steam_audio_sys::ffi;
+
+use crate::{prelude::*, Orientation};
+
+pub struct AmbisonicsRotationParams {
+    /// Orientation of the listener, the soundfield gets rotated into this space.
+    pub orientation: Orientation,
+    pub order: u8,
+}
+
+impl Default for AmbisonicsRotationParams {
+    fn default() -> Self {
+        Self {
+            orientation: Orientation::default(),
+            order: 1,
+        }
+    }
+}
+
+impl From<&AmbisonicsRotationParams> for ffi::IPLAmbisonicsRotationEffectParams {
+    fn from(ambisonics_rotation_params: &AmbisonicsRotationParams) -> Self {
+        ffi::IPLAmbisonicsRotationEffectParams {
+            orientation: ambisonics_rotation_params.orientation.clone().into(),
+            order: ambisonics_rotation_params.order as i32,
+        }
+    }
+}
+
+/// Rotates a world space ambisonic soundfield so it is relative to the listener.
+///
+/// Useful when everything gets mixed into a shared ambisonic bus, the bus only
+/// needs to be rotated once per frame before it is decoded.
+///
+/// This effect can't be applied in-place, the output has to be a separate buffer.
+pub struct AmbisonicsRotation {
+    inner: ffi::IPLAmbisonicsRotationEffect,
+    audio_settings: AudioSettings,
+    max_order: u8,
+}
+
+unsafe impl Send for AmbisonicsRotation {}
+unsafe impl Sync for AmbisonicsRotation {}
+
+impl crate::SteamAudioObject for AmbisonicsRotation {
+    type Object = ffi::IPLAmbisonicsRotationEffect;
+    fn inner_raw(&self) -> Self::Object {
+        assert!(!self.inner.is_null());
+        self.inner
+    }
+    fn inner_mut(&mut self) -> *mut Self::Object {
+        std::ptr::addr_of_mut!(self.inner)
+    }
+}
+
+impl AmbisonicsRotation {
+    pub fn new(
+        context: &Context,
+        audio_settings: &AudioSettings,
+        max_order: u8,
+    ) -> Result<Self, SteamAudioError> {
+        let mut effect = Self {
+            inner: std::ptr::null_mut(),
+            audio_settings: *audio_settings,
+            max_order,
+        };
+
+        let mut effect_settings = ffi::IPLAmbisonicsRotationEffectSettings {
+            maxOrder: max_order as i32,
+        };
+
+        unsafe {
+            match ffi::iplAmbisonicsRotationEffectCreate(
+                context.inner_raw(),
+                &mut audio_settings.into(),
+                &mut effect_settings,
+                effect.inner_mut(),
+            ) {
+                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(effect),
+                err => Err(SteamAudioError::IPLError(err)),
+            }
+        }
+    }
+
+    pub fn max_order(&self) -> u8 {
+        self.max_order
+    }
+
+    /// Clear any internal processing state, e.g. when the listener teleports.
+    pub fn reset(&self) {
+        unsafe {
+            ffi::iplAmbisonicsRotationEffectReset(self.inner_raw());
+        }
+    }
+}
+
+impl crate::effect::private::RawEffect<AmbisonicsRotationParams> for AmbisonicsRotation {
+    fn audio_settings(&self) -> &AudioSettings {
+        &self.audio_settings
+    }
+
+    fn channels(
+        &self,
+        params: &AmbisonicsRotationParams,
+    ) -> Result<(usize, usize), SteamAudioError> {
+        SteamAudioError::check_ambisonics_order(self.max_order, params.order)?;
+        let channels = crate::ambisonic_order_channels(params.order);
+        Ok((channels, channels))
+    }
+
+    unsafe fn apply_ffi(
+        &self,
+        params: &AmbisonicsRotationParams,
+        input: &mut ffi::IPLAudioBuffer,
+        output: &mut ffi::IPLAudioBuffer,
+    ) {
+        let mut ipl_params: ffi::IPLAmbisonicsRotationEffectParams = params.into();
+        ffi::iplAmbisonicsRotationEffectApply(self.inner_raw(), &mut ipl_params, input, output);
+    }
+}
+
+impl Drop for AmbisonicsRotation {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::iplAmbisonicsRotationEffectRelease(self.inner_mut());
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use steam_audio_sys::ffi;
 
-use crate::prelude::*;
+use crate::{prelude::*, Orientation};
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BinauralParams {
     pub direction: [f32; 3],
     pub interpolation: HRTFInterpolation,
@@ -19,10 +20,30 @@ impl Default for BinauralParams {
 }
 
 impl BinauralParams {
+    /// Parameters for a source at `source_position` heard by `listener`.
+    pub fn from_positions(
+        context: &Context,
+        listener: &Orientation,
+        source_position: impl Into<[f32; 3]>,
+    ) -> Self {
+        Self {
+            direction: listener.relative_direction(context, source_position),
+            ..Default::default()
+        }
+    }
+
+    /// Parameters for a source in `direction`, relative to the listener.
+    pub fn from_direction(direction: impl Into<[f32; 3]>) -> Self {
+        Self {
+            direction: direction.into(),
+            ..Default::default()
+        }
+    }
+
     pub fn merge(&self, hrtf: ffi::IPLHRTF) -> ffi::IPLBinauralEffectParams {
         ffi::IPLBinauralEffectParams {
             direction: self.direction.into(),
-            hrtf: hrtf,
+            hrtf,
             interpolation: self.interpolation.into(),
             spatialBlend: self.spatial_blend,
             peakDelays: std::ptr::null_mut(),
@@ -32,7 +53,9 @@ impl BinauralParams {
 
 pub struct BinauralEffect {
     inner: ffi::IPLBinauralEffect,
-    hrtf: ffi::IPLHRTF,
+    audio_settings: AudioSettings,
+    // Retained, every apply hands it to steam audio again.
+    hrtf: HRTF,
 }
 
 unsafe impl Send for BinauralEffect {}
@@ -57,7 +80,8 @@ impl BinauralEffect {
     ) -> Result<Self, SteamAudioError> {
         let mut effect = Self {
             inner: std::ptr::null_mut(),
-            hrtf: hrtf.inner_raw(),
+            audio_settings: *audio_settings,
+            hrtf: hrtf.retain(),
         };
 
         let mut effect_settings = ffi::IPLBinauralEffectSettings {
@@ -76,55 +100,25 @@ impl BinauralEffect {
             }
         }
     }
+}
 
-    pub fn apply_to_buffer(
-        &self,
-        params: &BinauralParams,
-        frame: &mut DeinterleavedFrame,
-        output_buffer: &mut DeinterleavedFrame,
-    ) -> Result<(), SteamAudioError> {
-        assert_eq!(frame.channels(), 1);
-        assert_eq!(output_buffer.channels(), 2);
-
-        let mut input_ffi_buffer = ffi::IPLAudioBuffer {
-            numChannels: frame.channels() as i32,
-            numSamples: frame.frame_size() as i32,
-            data: unsafe { frame.ptrs() },
-        };
-
-        let mut output_ffi_buffer = ffi::IPLAudioBuffer {
-            numChannels: output_buffer.channels() as i32,
-            numSamples: output_buffer.frame_size() as i32,
-            data: unsafe { output_buffer.ptrs() },
-        };
-
-        let mut ipl_params = params.merge(self.hrtf);
-
-        unsafe {
-            let _effect_state = ffi::iplBinauralEffectApply(
-                self.inner_raw(),
-                &mut ipl_params,
-                &mut input_ffi_buffer,
-                &mut output_ffi_buffer,
-            );
-        }
+impl crate::effect::private::RawEffect<BinauralParams> for BinauralEffect {
+    fn audio_settings(&self) -> &AudioSettings {
+        &self.audio_settings
+    }
 
-        Ok(())
+    fn channels(&self, _params: &BinauralParams) -> Result<(usize, usize), SteamAudioError> {
+        Ok((1, 2))
     }
 
-    pub fn apply(
+    unsafe fn apply_ffi(
         &self,
-        audio_settings: &AudioSettings,
         params: &BinauralParams,
-        frame: &mut DeinterleavedFrame,
-    ) -> Result<DeinterleavedFrame, SteamAudioError> {
-        let mut output_buffer = DeinterleavedFrame::new(
-            audio_settings.frame_size() as usize,
-            2,
-            audio_settings.sampling_rate(),
-        );
-        self.apply_to_buffer(params, frame, &mut output_buffer)?;
-        Ok(output_buffer)
+        input: &mut ffi::IPLAudioBuffer,
+        output: &mut ffi::IPLAudioBuffer,
+    ) {
+        let mut ipl_params = params.merge(self.hrtf.inner_raw());
+        ffi::iplBinauralEffectApply(self.inner_raw(), &mut ipl_params, input, output);
     }
 }
 
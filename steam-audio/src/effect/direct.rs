@@ -20,9 +20,18 @@ impl Default for DirectSimulationFlags {
     }
 }
 
-impl Into<ffi::IPLDirectSimulationFlags> for DirectSimulationFlags {
-    fn into(self) -> ffi::IPLDirectSimulationFlags {
-        ffi::IPLDirectSimulationFlags(self.bits())
+#[cfg(feature = "serde")]
+crate::serde_flags::serde_flags!(DirectSimulationFlags {
+    DISTANCE_ATTENUATION,
+    AIR_ABSORPTION,
+    DIRECTIVITY,
+    OCCLUSION,
+    TRANSMISSION
+});
+
+impl From<DirectSimulationFlags> for ffi::IPLDirectSimulationFlags {
+    fn from(direct_simulation_flags: DirectSimulationFlags) -> Self {
+        ffi::IPLDirectSimulationFlags(direct_simulation_flags.bits())
     }
 }
 
@@ -43,9 +52,18 @@ impl Default for DirectEffectFlags {
     }
 }
 
-impl Into<ffi::IPLDirectEffectFlags> for DirectEffectFlags {
-    fn into(self) -> ffi::IPLDirectEffectFlags {
-        ffi::IPLDirectEffectFlags(self.bits())
+#[cfg(feature = "serde")]
+crate::serde_flags::serde_flags!(DirectEffectFlags {
+    DISTANCE_ATTENUATION,
+    AIR_ABSORPTION,
+    DIRECTIVITY,
+    OCCLUSION,
+    TRANSMISSION
+});
+
+impl From<DirectEffectFlags> for ffi::IPLDirectEffectFlags {
+    fn from(direct_effect_flags: DirectEffectFlags) -> Self {
+        ffi::IPLDirectEffectFlags(direct_effect_flags.bits())
     }
 }
 
@@ -55,25 +73,21 @@ impl From<ffi::IPLDirectEffectFlags> for DirectEffectFlags {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransmissionType {
+    #[default]
     FrequencyIndependent,
     FrequencyDependent,
 }
 
-impl Default for TransmissionType {
-    fn default() -> Self {
-        Self::FrequencyIndependent
-    }
-}
-
-impl Into<ffi::IPLTransmissionType> for TransmissionType {
-    fn into(self) -> ffi::IPLTransmissionType {
-        match self {
-            Self::FrequencyIndependent => {
+impl From<TransmissionType> for ffi::IPLTransmissionType {
+    fn from(transmission_type: TransmissionType) -> Self {
+        match transmission_type {
+            TransmissionType::FrequencyIndependent => {
                 ffi::IPLTransmissionType::IPL_TRANSMISSIONTYPE_FREQINDEPENDENT
             }
-            Self::FrequencyDependent => {
+            TransmissionType::FrequencyDependent => {
                 ffi::IPLTransmissionType::IPL_TRANSMISSIONTYPE_FREQDEPENDENT
             }
         }
@@ -94,6 +108,7 @@ impl From<ffi::IPLTransmissionType> for TransmissionType {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DirectEffectParams {
     pub flags: DirectEffectFlags,
     pub transmission_type: TransmissionType,
@@ -122,18 +137,18 @@ impl Default for DirectEffectParams {
     }
 }
 
-impl Into<ffi::IPLDirectEffectParams> for &DirectEffectParams {
-    fn into(self) -> ffi::IPLDirectEffectParams {
+impl From<&DirectEffectParams> for ffi::IPLDirectEffectParams {
+    fn from(direct_effect_params: &DirectEffectParams) -> Self {
         ffi::IPLDirectEffectParams {
-            flags: self.flags.into(),
-            transmissionType: self.transmission_type.into(),
+            flags: direct_effect_params.flags.into(),
+            transmissionType: direct_effect_params.transmission_type.into(),
 
-            distanceAttenuation: self.distance_attenuation,
-            directivity: self.directivity,
-            occlusion: self.occlusion,
+            distanceAttenuation: direct_effect_params.distance_attenuation,
+            directivity: direct_effect_params.directivity,
+            occlusion: direct_effect_params.occlusion,
 
-            airAbsorption: self.air_absorption,
-            transmission: self.transmission,
+            airAbsorption: direct_effect_params.air_absorption,
+            transmission: direct_effect_params.transmission,
         }
     }
 }
@@ -156,6 +171,7 @@ impl From<ffi::IPLDirectEffectParams> for DirectEffectParams {
 
 pub struct DirectEffect {
     inner: ffi::IPLDirectEffect,
+    audio_settings: AudioSettings,
     channels: u16,
 }
 
@@ -181,6 +197,7 @@ impl DirectEffect {
     ) -> Result<Self, SteamAudioError> {
         let mut effect = Self {
             inner: std::ptr::null_mut(),
+            audio_settings: *audio_settings,
             channels: num_channels,
         };
 
@@ -200,55 +217,25 @@ impl DirectEffect {
             }
         }
     }
+}
 
-    pub fn apply_to_buffer(
-        &self,
-        params: &DirectEffectParams,
-        mut frame: DeinterleavedFrame,
-        output_buffer: &mut DeinterleavedFrame,
-    ) -> Result<(), SteamAudioError> {
-        assert_eq!(frame.channels(), self.channels);
-        assert_eq!(output_buffer.channels(), self.channels);
-
-        let mut input_ffi_buffer = ffi::IPLAudioBuffer {
-            numChannels: frame.channels() as i32,
-            numSamples: frame.frame_size() as i32,
-            data: unsafe { frame.ptrs() },
-        };
-
-        let mut output_ffi_buffer = ffi::IPLAudioBuffer {
-            numChannels: output_buffer.channels() as i32,
-            numSamples: output_buffer.frame_size() as i32,
-            data: unsafe { output_buffer.ptrs() },
-        };
-
-        let mut ipl_params: ffi::IPLDirectEffectParams = params.into();
-
-        unsafe {
-            let _effect_state = ffi::iplDirectEffectApply(
-                self.inner_raw(),
-                &mut ipl_params,
-                &mut input_ffi_buffer,
-                &mut output_ffi_buffer,
-            );
-        }
+impl crate::effect::private::RawEffect<DirectEffectParams> for DirectEffect {
+    fn audio_settings(&self) -> &AudioSettings {
+        &self.audio_settings
+    }
 
-        Ok(())
+    fn channels(&self, _params: &DirectEffectParams) -> Result<(usize, usize), SteamAudioError> {
+        Ok((self.channels as usize, self.channels as usize))
     }
 
-    pub fn apply(
+    unsafe fn apply_ffi(
         &self,
-        audio_settings: &AudioSettings,
         params: &DirectEffectParams,
-        frame: DeinterleavedFrame,
-    ) -> Result<DeinterleavedFrame, SteamAudioError> {
-        let mut output_buffer = DeinterleavedFrame::new(
-            audio_settings.frame_size() as usize,
-            self.channels as u16,
-            audio_settings.sampling_rate(),
-        );
-        self.apply_to_buffer(params, frame, &mut output_buffer)?;
-        Ok(output_buffer)
+        input: &mut ffi::IPLAudioBuffer,
+        output: &mut ffi::IPLAudioBuffer,
+    ) {
+        let mut ipl_params: ffi::IPLDirectEffectParams = params.into();
+        ffi::iplDirectEffectApply(self.inner_raw(), &mut ipl_params, input, output);
     }
 }
 
@@ -1,4 +1,124 @@
+pub mod ambisonics;
 pub mod binaural;
 
 pub mod direct;
+pub mod panning;
 pub mod reflection;
+pub mod virtual_surround;
+
+use crate::audio_buffer::RawBuffer;
+use crate::prelude::*;
+
+/// Applying an effect that turns one buffer into another.
+///
+/// Every buffer is checked against the effect's [`AudioSettings`] and the
+/// channels its params need before it is handed to steam audio.
+pub trait Effect<P>: private::RawEffect<P> {
+    /// Apply the effect to `frame`, writing the result into `output_buffer`.
+    fn apply_to_buffer(
+        &self,
+        params: &P,
+        frame: &mut DeinterleavedFrame,
+        output_buffer: &mut DeinterleavedFrame,
+    ) -> Result<(), SteamAudioError> {
+        self.audio_settings()
+            .check_sample_rate(frame.sample_rate())?;
+        self.audio_settings()
+            .check_sample_rate(output_buffer.sample_rate())?;
+        apply_checked(
+            self,
+            params,
+            RawBuffer::from_frame(frame)?,
+            RawBuffer::from_frame(output_buffer)?,
+        )
+    }
+
+    /// Same as [`Effect::apply_to_buffer`] but on borrowed channel slices, nothing is allocated.
+    fn apply_to_slices(
+        &self,
+        params: &P,
+        input: &[&[f32]],
+        output: &mut [&mut [f32]],
+    ) -> Result<(), SteamAudioError> {
+        apply_checked(
+            self,
+            params,
+            RawBuffer::from_slices(input)?,
+            RawBuffer::from_slices_mut(output)?,
+        )
+    }
+
+    /// Apply the effect to `frame`, allocating a new output frame.
+    fn apply(
+        &self,
+        audio_settings: &AudioSettings,
+        params: &P,
+        frame: &mut DeinterleavedFrame,
+    ) -> Result<DeinterleavedFrame, SteamAudioError> {
+        let (_, output_channels) = self.channels(params)?;
+        let mut output_buffer = DeinterleavedFrame::new(
+            audio_settings.frame_size() as usize,
+            output_channels as u16,
+            audio_settings.sampling_rate(),
+        );
+        self.apply_to_buffer(params, frame, &mut output_buffer)?;
+        Ok(output_buffer)
+    }
+}
+
+impl<P, T: private::RawEffect<P> + ?Sized> Effect<P> for T {}
+
+fn apply_checked<P, E: private::RawEffect<P> + ?Sized>(
+    effect: &E,
+    params: &P,
+    mut input: RawBuffer,
+    mut output: RawBuffer,
+) -> Result<(), SteamAudioError> {
+    check_buffers(effect, params, &input, &output)?;
+
+    unsafe {
+        effect.apply_ffi(params, &mut input.as_ffi(), &mut output.as_ffi());
+    }
+
+    Ok(())
+}
+
+/// Check `input` and `output` can be handed to `effect` with `params`.
+pub(crate) fn check_buffers<P, E: private::RawEffect<P> + ?Sized>(
+    effect: &E,
+    params: &P,
+    input: &RawBuffer,
+    output: &RawBuffer,
+) -> Result<(), SteamAudioError> {
+    let (input_channels, output_channels) = effect.channels(params)?;
+    input.check_channels(input_channels)?;
+    output.check_channels(output_channels)?;
+    input.check_num_samples(effect.audio_settings().frame_size() as usize)?;
+    input.check_frame_size(output)
+}
+
+pub(crate) mod private {
+    use steam_audio_sys::ffi;
+
+    use crate::prelude::*;
+
+    /// What an effect has to provide to get [`super::Effect`], kept private so
+    /// the checks can't be skipped.
+    pub trait RawEffect<P> {
+        /// Settings the effect was created with.
+        fn audio_settings(&self) -> &AudioSettings;
+
+        /// Input and output channels needed for `params`, or why `params` can't be used.
+        fn channels(&self, params: &P) -> Result<(usize, usize), SteamAudioError>;
+
+        /// # Safety
+        ///
+        /// `input` and `output` have to match [`RawEffect::channels`] and the frame size.
+        unsafe fn apply_ffi(
+            &self,
+            params: &P,
+            input: &mut ffi::IPLAudioBuffer,
+            output: &mut ffi::IPLAudioBuffer,
+        );
+    }
+}
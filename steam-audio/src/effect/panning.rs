@@ -0,0 +1,154 @@
+use steam_audio_sys::ffi;
+
+use crate::prelude::*;
+
+pub struct PanningParams {
+    /// Unit vector pointing from the listener towards the source.
+    pub direction: [f32; 3],
+}
+
+impl Default for PanningParams {
+    fn default() -> Self {
+        Self {
+            direction: [0.0, 0.0, -1.0],
+        }
+    }
+}
+
+impl From<&PanningParams> for ffi::IPLPanningEffectParams {
+    fn from(panning_params: &PanningParams) -> Self {
+        ffi::IPLPanningEffectParams {
+            direction: panning_params.direction.into(),
+        }
+    }
+}
+
+/// Pans a mono point source onto a speaker layout, for when the output isn't headphones.
+pub struct PanningEffect {
+    inner: ffi::IPLPanningEffect,
+    audio_settings: AudioSettings,
+    speaker_layout: SpeakerLayout,
+}
+
+unsafe impl Send for PanningEffect {}
+unsafe impl Sync for PanningEffect {}
+
+impl crate::SteamAudioObject for PanningEffect {
+    type Object = ffi::IPLPanningEffect;
+    fn inner_raw(&self) -> Self::Object {
+        assert!(!self.inner.is_null());
+        self.inner
+    }
+    fn inner_mut(&mut self) -> *mut Self::Object {
+        std::ptr::addr_of_mut!(self.inner)
+    }
+}
+
+impl PanningEffect {
+    pub fn new(
+        context: &Context,
+        audio_settings: &AudioSettings,
+        speaker_layout: &SpeakerLayout,
+    ) -> Result<Self, SteamAudioError> {
+        let mut effect = Self {
+            inner: std::ptr::null_mut(),
+            audio_settings: *audio_settings,
+            speaker_layout: speaker_layout.clone(),
+        };
+
+        let mut effect_settings = ffi::IPLPanningEffectSettings {
+            speakerLayout: speaker_layout.into(),
+        };
+
+        unsafe {
+            match ffi::iplPanningEffectCreate(
+                context.inner_raw(),
+                &mut audio_settings.into(),
+                &mut effect_settings,
+                effect.inner_mut(),
+            ) {
+                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(effect),
+                err => Err(SteamAudioError::IPLError(err)),
+            }
+        }
+    }
+
+    pub fn speaker_layout(&self) -> &SpeakerLayout {
+        &self.speaker_layout
+    }
+
+    pub fn reset(&self) {
+        unsafe {
+            ffi::iplPanningEffectReset(self.inner_raw());
+        }
+    }
+}
+
+impl crate::effect::private::RawEffect<PanningParams> for PanningEffect {
+    fn audio_settings(&self) -> &AudioSettings {
+        &self.audio_settings
+    }
+
+    fn channels(&self, _params: &PanningParams) -> Result<(usize, usize), SteamAudioError> {
+        Ok((1, self.speaker_layout.channels() as usize))
+    }
+
+    unsafe fn apply_ffi(
+        &self,
+        params: &PanningParams,
+        input: &mut ffi::IPLAudioBuffer,
+        output: &mut ffi::IPLAudioBuffer,
+    ) {
+        let mut ipl_params: ffi::IPLPanningEffectParams = params.into();
+        ffi::iplPanningEffectApply(self.inner_raw(), &mut ipl_params, input, output);
+    }
+}
+
+impl Drop for PanningEffect {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::iplPanningEffectRelease(self.inner_mut());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_slices_of_the_wrong_frame_size() {
+        let context = Context::new(&ContextSettings::default()).unwrap();
+        let audio_settings = AudioSettings::new(48000, 256).unwrap();
+        let effect = PanningEffect::new(&context, &audio_settings, &SpeakerLayout::Stereo).unwrap();
+        let params = PanningParams::default();
+
+        for frame_size in [128, 512] {
+            let input = vec![0.0; frame_size];
+            let mut left = vec![0.0; frame_size];
+            let mut right = vec![0.0; frame_size];
+            let result = effect.apply_to_slices(&params, &[&input], &mut [&mut left, &mut right]);
+            assert!(matches!(
+                result,
+                Err(SteamAudioError::FrameSizeMismatch { expected: 256, got }) if got == frame_size
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_frames_at_another_sample_rate() {
+        let context = Context::new(&ContextSettings::default()).unwrap();
+        let audio_settings = AudioSettings::new(48000, 256).unwrap();
+        let effect = PanningEffect::new(&context, &audio_settings, &SpeakerLayout::Stereo).unwrap();
+
+        let mut frame = DeinterleavedFrame::new(256, 1, 44100);
+        let mut output = DeinterleavedFrame::new(256, 2, 48000);
+        assert!(matches!(
+            effect.apply_to_buffer(&PanningParams::default(), &mut frame, &mut output),
+            Err(SteamAudioError::SampleRateMismatch {
+                expected: 48000,
+                got: 44100
+            })
+        ));
+    }
+}
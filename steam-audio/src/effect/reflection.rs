@@ -1 +1,472 @@
+use std::marker::PhantomData;
 
+use steam_audio_sys::ffi;
+
+use crate::audio_buffer::RawBuffer;
+use crate::prelude::*;
+use crate::simulation::simulation::ReflectionEffectType;
+use crate::simulation::source::Source;
+
+#[derive(Debug, Copy, Clone)]
+pub struct ReflectionEffectSettings {
+    pub reflection_type: ReflectionEffectType,
+    /// Number of samples per channel in the impulse response.
+    pub ir_size: u32,
+    /// Number of channels in the impulse response, see [`crate::ambisonic_order_channels`].
+    pub num_channels: u16,
+}
+
+impl ReflectionEffectSettings {
+    /// Settings matching the impulse responses generated by a simulator.
+    pub fn from_simulation_settings(settings: &SimulationSettings) -> Self {
+        Self {
+            reflection_type: settings.reflection_type,
+            ir_size: (settings.max_duration * settings.sampling_rate as f32).ceil() as u32,
+            num_channels: crate::ambisonic_order_channels(settings.max_order) as u16,
+        }
+    }
+
+    // Effects and mixers assume every impulse response they get has their layout.
+    fn check_params(&self, params: &ReflectionEffectParams) -> Result<(), SteamAudioError> {
+        if params.reflection_type != self.reflection_type {
+            return Err(SteamAudioError::ReflectionTypeMismatch {
+                expected: self.reflection_type,
+                got: params.reflection_type,
+            });
+        }
+
+        if params.ir_size != self.ir_size {
+            return Err(SteamAudioError::IrSizeMismatch {
+                expected: self.ir_size,
+                got: params.ir_size,
+            });
+        }
+
+        SteamAudioError::check_channels(self.num_channels as usize, params.num_channels as usize)?;
+
+        // Steam audio reads the impulse response without checking it's there.
+        let needs_ir = matches!(
+            self.reflection_type,
+            ReflectionEffectType::Convolution | ReflectionEffectType::Hybrid
+        );
+        if needs_ir && params.ir.is_null() {
+            return Err(SteamAudioError::MissingImpulseResponse);
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&ReflectionEffectSettings> for ffi::IPLReflectionEffectSettings {
+    fn from(reflection_effect_settings: &ReflectionEffectSettings) -> Self {
+        ffi::IPLReflectionEffectSettings {
+            type_: reflection_effect_settings.reflection_type.into(),
+            irSize: reflection_effect_settings.ir_size as i32,
+            numChannels: reflection_effect_settings.num_channels as i32,
+        }
+    }
+}
+
+/// Simulated reflections for a source, taken from [`SimulationOutputs`].
+///
+/// Borrows the [`Source`] that owns the impulse response, so it can't be
+/// applied after the source is gone. The impulse response layout is the one
+/// of the [`Simulator`] the source was created with.
+///
+/// [`SimulationOutputs`]: crate::simulation::source::SimulationOutputs
+#[derive(Debug, Clone)]
+pub struct ReflectionEffectParams<'a> {
+    reflection_type: ReflectionEffectType,
+    // Impulse response owned by the source.
+    ir: ffi::IPLReflectionEffectIR,
+    pub reverb_times: [f32; 3],
+    pub eq: [f32; 3],
+    pub delay: u32,
+    num_channels: u16,
+    ir_size: u32,
+    // TODO: Expose tanDevice and tanSlot.
+    _source: PhantomData<&'a Source>,
+}
+
+impl<'a> ReflectionEffectParams<'a> {
+    /// The simulator doesn't fill in the layout, `layout` has to be the one of
+    /// the simulator that wrote `other`.
+    pub(crate) fn from_source(
+        _source: &'a Source,
+        layout: &ReflectionEffectSettings,
+        other: ffi::IPLReflectionEffectParams,
+    ) -> Self {
+        Self {
+            reflection_type: layout.reflection_type,
+            ir: other.ir,
+            reverb_times: other.reverbTimes,
+            eq: other.eq,
+            delay: other.delay as u32,
+            num_channels: layout.num_channels,
+            ir_size: layout.ir_size,
+            _source: PhantomData,
+        }
+    }
+
+    pub fn reflection_type(&self) -> ReflectionEffectType {
+        self.reflection_type
+    }
+
+    pub fn num_channels(&self) -> u16 {
+        self.num_channels
+    }
+
+    pub fn ir_size(&self) -> u32 {
+        self.ir_size
+    }
+}
+
+impl From<&ReflectionEffectParams<'_>> for ffi::IPLReflectionEffectParams {
+    fn from(reflection_effect_params: &ReflectionEffectParams<'_>) -> Self {
+        ffi::IPLReflectionEffectParams {
+            type_: reflection_effect_params.reflection_type.into(),
+            ir: reflection_effect_params.ir,
+            reverbTimes: reflection_effect_params.reverb_times,
+            eq: reflection_effect_params.eq,
+            delay: reflection_effect_params.delay as i32,
+            numChannels: reflection_effect_params.num_channels as i32,
+            irSize: reflection_effect_params.ir_size as i32,
+            tanDevice: std::ptr::null_mut(),
+            tanSlot: 0,
+        }
+    }
+}
+
+/// Applies simulated reflections to a mono source, producing an ambisonic soundfield.
+pub struct ReflectionEffect {
+    inner: ffi::IPLReflectionEffect,
+    audio_settings: AudioSettings,
+    settings: ReflectionEffectSettings,
+}
+
+unsafe impl Send for ReflectionEffect {}
+unsafe impl Sync for ReflectionEffect {}
+
+impl crate::SteamAudioObject for ReflectionEffect {
+    type Object = ffi::IPLReflectionEffect;
+    fn inner_raw(&self) -> Self::Object {
+        assert!(!self.inner.is_null());
+        self.inner
+    }
+    fn inner_mut(&mut self) -> *mut Self::Object {
+        std::ptr::addr_of_mut!(self.inner)
+    }
+}
+
+impl ReflectionEffect {
+    pub fn new(
+        context: &Context,
+        audio_settings: &AudioSettings,
+        settings: &ReflectionEffectSettings,
+    ) -> Result<Self, SteamAudioError> {
+        let mut effect = Self {
+            inner: std::ptr::null_mut(),
+            audio_settings: *audio_settings,
+            settings: *settings,
+        };
+
+        let mut effect_settings: ffi::IPLReflectionEffectSettings = settings.into();
+
+        unsafe {
+            match ffi::iplReflectionEffectCreate(
+                context.inner_raw(),
+                &mut audio_settings.into(),
+                &mut effect_settings,
+                effect.inner_mut(),
+            ) {
+                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(effect),
+                err => Err(SteamAudioError::IPLError(err)),
+            }
+        }
+    }
+
+    pub fn settings(&self) -> &ReflectionEffectSettings {
+        &self.settings
+    }
+
+    pub fn reset(&self) {
+        unsafe {
+            ffi::iplReflectionEffectReset(self.inner_raw());
+        }
+    }
+
+    // Mixes into `mixer` instead of writing `output` when it isn't null.
+    unsafe fn apply_ffi_mixed(
+        &self,
+        params: &ReflectionEffectParams,
+        input: &mut ffi::IPLAudioBuffer,
+        output: &mut ffi::IPLAudioBuffer,
+        mixer: ffi::IPLReflectionMixer,
+    ) {
+        let mut ipl_params: ffi::IPLReflectionEffectParams = params.into();
+        ffi::iplReflectionEffectApply(self.inner_raw(), &mut ipl_params, input, output, mixer);
+    }
+}
+
+impl<'a> crate::effect::private::RawEffect<ReflectionEffectParams<'a>> for ReflectionEffect {
+    fn audio_settings(&self) -> &AudioSettings {
+        &self.audio_settings
+    }
+
+    fn channels(
+        &self,
+        params: &ReflectionEffectParams<'a>,
+    ) -> Result<(usize, usize), SteamAudioError> {
+        self.settings.check_params(params)?;
+        Ok((1, self.settings.num_channels as usize))
+    }
+
+    unsafe fn apply_ffi(
+        &self,
+        params: &ReflectionEffectParams<'a>,
+        input: &mut ffi::IPLAudioBuffer,
+        output: &mut ffi::IPLAudioBuffer,
+    ) {
+        self.apply_ffi_mixed(params, input, output, std::ptr::null_mut());
+    }
+}
+
+impl Drop for ReflectionEffect {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::iplReflectionEffectRelease(self.inner_mut());
+        }
+    }
+}
+
+/// Accumulates the output of many [`ReflectionEffect`]s so the convolution
+/// only has to be rendered once per frame.
+///
+/// ```ignore
+/// let mut mix = mixer.begin();
+/// for (effect, params, frame) in sources.iter_mut() {
+///     mix.add(effect, params, frame)?;
+/// }
+/// mix.finish(&mut ambisonic_output)?;
+/// ```
+pub struct ReflectionMixer {
+    inner: ffi::IPLReflectionMixer,
+    audio_settings: AudioSettings,
+    settings: ReflectionEffectSettings,
+
+    // Steam Audio still wants an output buffer when mixing, nothing is written to it.
+    scratch: DeinterleavedFrame,
+}
+
+unsafe impl Send for ReflectionMixer {}
+unsafe impl Sync for ReflectionMixer {}
+
+impl crate::SteamAudioObject for ReflectionMixer {
+    type Object = ffi::IPLReflectionMixer;
+    fn inner_raw(&self) -> Self::Object {
+        assert!(!self.inner.is_null());
+        self.inner
+    }
+    fn inner_mut(&mut self) -> *mut Self::Object {
+        std::ptr::addr_of_mut!(self.inner)
+    }
+}
+
+impl ReflectionMixer {
+    /// `settings` must match the settings of the effects that will be mixed in.
+    pub fn new(
+        context: &Context,
+        audio_settings: &AudioSettings,
+        settings: &ReflectionEffectSettings,
+    ) -> Result<Self, SteamAudioError> {
+        let mut mixer = Self {
+            inner: std::ptr::null_mut(),
+            audio_settings: *audio_settings,
+            settings: *settings,
+            scratch: DeinterleavedFrame::new(
+                audio_settings.frame_size() as usize,
+                settings.num_channels,
+                audio_settings.sampling_rate(),
+            ),
+        };
+
+        let mut effect_settings: ffi::IPLReflectionEffectSettings = settings.into();
+
+        unsafe {
+            match ffi::iplReflectionMixerCreate(
+                context.inner_raw(),
+                &mut audio_settings.into(),
+                &mut effect_settings,
+                mixer.inner_mut(),
+            ) {
+                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(mixer),
+                err => Err(SteamAudioError::IPLError(err)),
+            }
+        }
+    }
+
+    pub fn settings(&self) -> &ReflectionEffectSettings {
+        &self.settings
+    }
+
+    /// Start mixing a new frame.
+    pub fn begin(&mut self) -> ReflectionMix<'_> {
+        ReflectionMix {
+            mixer: self,
+            finished: false,
+        }
+    }
+
+    pub fn reset(&self) {
+        unsafe {
+            ffi::iplReflectionMixerReset(self.inner_raw());
+        }
+    }
+}
+
+impl Drop for ReflectionMixer {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::iplReflectionMixerRelease(self.inner_mut());
+        }
+    }
+}
+
+/// A single frame being mixed by a [`ReflectionMixer`].
+///
+/// Dropping this without calling [`ReflectionMix::finish`] resets the mixer so
+/// a partial mix doesn't leak into the next frame.
+pub struct ReflectionMix<'a> {
+    mixer: &'a mut ReflectionMixer,
+    finished: bool,
+}
+
+impl<'a> ReflectionMix<'a> {
+    /// Apply `effect` to a mono `frame` and accumulate the result into the mixer.
+    pub fn add(
+        &mut self,
+        effect: &ReflectionEffect,
+        params: &ReflectionEffectParams,
+        frame: &mut DeinterleavedFrame,
+    ) -> Result<(), SteamAudioError> {
+        SteamAudioError::check_channels(
+            self.mixer.settings.num_channels as usize,
+            effect.settings().num_channels as usize,
+        )?;
+        self.mixer.settings.check_params(params)?;
+        self.mixer
+            .audio_settings
+            .check_sample_rate(frame.sample_rate())?;
+
+        let mixer = self.mixer.inner_raw();
+        let mut input = RawBuffer::from_frame(frame)?;
+        let mut output = RawBuffer::from_frame(&mut self.mixer.scratch)?;
+        crate::effect::check_buffers(effect, params, &input, &output)?;
+
+        unsafe {
+            effect.apply_ffi_mixed(params, &mut input.as_ffi(), &mut output.as_ffi(), mixer);
+        }
+
+        Ok(())
+    }
+
+    /// Render everything mixed this frame into `output_buffer`.
+    pub fn finish(mut self, output_buffer: &mut DeinterleavedFrame) -> Result<(), SteamAudioError> {
+        self.mixer
+            .audio_settings
+            .check_sample_rate(output_buffer.sample_rate())?;
+        let mut output = RawBuffer::from_frame(output_buffer)?;
+        output.check_channels(self.mixer.settings.num_channels as usize)?;
+        output.check_num_samples(self.mixer.audio_settings.frame_size() as usize)?;
+        let mut ipl_params = ffi::IPLReflectionEffectParams {
+            type_: self.mixer.settings.reflection_type.into(),
+            ir: std::ptr::null_mut(),
+            reverbTimes: [0.0; 3],
+            eq: [0.0; 3],
+            delay: 0,
+            numChannels: self.mixer.settings.num_channels as i32,
+            irSize: self.mixer.settings.ir_size as i32,
+            tanDevice: std::ptr::null_mut(),
+            tanSlot: 0,
+        };
+
+        unsafe {
+            let _effect_state = ffi::iplReflectionMixerApply(
+                self.mixer.inner_raw(),
+                &mut ipl_params,
+                &mut output.as_ffi(),
+            );
+        }
+
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl<'a> Drop for ReflectionMix<'a> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.mixer.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_must_match_the_effect_layout() {
+        let settings = ReflectionEffectSettings {
+            reflection_type: ReflectionEffectType::Convolution,
+            ir_size: 48000,
+            num_channels: 4,
+        };
+        let params = ReflectionEffectParams {
+            reflection_type: ReflectionEffectType::Parametric,
+            ir: std::ptr::null_mut(),
+            reverb_times: [1.0; 3],
+            eq: [1.0; 3],
+            delay: 0,
+            num_channels: 4,
+            ir_size: 48000,
+            _source: PhantomData,
+        };
+        assert!(matches!(
+            settings.check_params(&params),
+            Err(SteamAudioError::ReflectionTypeMismatch {
+                expected: ReflectionEffectType::Convolution,
+                got: ReflectionEffectType::Parametric,
+            })
+        ));
+
+        let params = ReflectionEffectParams {
+            reflection_type: ReflectionEffectType::Convolution,
+            ir_size: 24000,
+            ..params
+        };
+        assert!(matches!(
+            settings.check_params(&params),
+            Err(SteamAudioError::IrSizeMismatch {
+                expected: 48000,
+                got: 24000
+            })
+        ));
+
+        let params = ReflectionEffectParams {
+            ir_size: 48000,
+            ..params
+        };
+        assert!(matches!(
+            settings.check_params(&params),
+            Err(SteamAudioError::MissingImpulseResponse)
+        ));
+
+        // Never read, steam audio isn't called.
+        let params = ReflectionEffectParams {
+            ir: std::ptr::NonNull::dangling().as_ptr(),
+            ..params
+        };
+        assert!(settings.check_params(&params).is_ok());
+    }
+}
@@ -0,0 +1,102 @@
+use steam_audio_sys::ffi;
+
+use crate::prelude::*;
+
+/// Renders a multichannel speaker bed binaurally, placing each channel at its
+/// virtual speaker position through the HRTF.
+///
+/// There are no per-frame params, pass `&()` to the [`Effect`] methods.
+pub struct VirtualSurroundEffect {
+    inner: ffi::IPLVirtualSurroundEffect,
+    audio_settings: AudioSettings,
+    // Retained, every apply hands it to steam audio again.
+    hrtf: HRTF,
+    speaker_layout: SpeakerLayout,
+}
+
+unsafe impl Send for VirtualSurroundEffect {}
+unsafe impl Sync for VirtualSurroundEffect {}
+
+impl crate::SteamAudioObject for VirtualSurroundEffect {
+    type Object = ffi::IPLVirtualSurroundEffect;
+    fn inner_raw(&self) -> Self::Object {
+        assert!(!self.inner.is_null());
+        self.inner
+    }
+    fn inner_mut(&mut self) -> *mut Self::Object {
+        std::ptr::addr_of_mut!(self.inner)
+    }
+}
+
+impl VirtualSurroundEffect {
+    pub fn new(
+        context: &Context,
+        audio_settings: &AudioSettings,
+        speaker_layout: &SpeakerLayout,
+        hrtf: &HRTF,
+    ) -> Result<Self, SteamAudioError> {
+        let mut effect = Self {
+            inner: std::ptr::null_mut(),
+            audio_settings: *audio_settings,
+            hrtf: hrtf.retain(),
+            speaker_layout: speaker_layout.clone(),
+        };
+
+        let mut effect_settings = ffi::IPLVirtualSurroundEffectSettings {
+            speakerLayout: speaker_layout.into(),
+            hrtf: hrtf.inner_raw(),
+        };
+
+        unsafe {
+            match ffi::iplVirtualSurroundEffectCreate(
+                context.inner_raw(),
+                &mut audio_settings.into(),
+                &mut effect_settings,
+                effect.inner_mut(),
+            ) {
+                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(effect),
+                err => Err(SteamAudioError::IPLError(err)),
+            }
+        }
+    }
+
+    pub fn speaker_layout(&self) -> &SpeakerLayout {
+        &self.speaker_layout
+    }
+
+    pub fn reset(&self) {
+        unsafe {
+            ffi::iplVirtualSurroundEffectReset(self.inner_raw());
+        }
+    }
+}
+
+impl crate::effect::private::RawEffect<()> for VirtualSurroundEffect {
+    fn audio_settings(&self) -> &AudioSettings {
+        &self.audio_settings
+    }
+
+    fn channels(&self, _params: &()) -> Result<(usize, usize), SteamAudioError> {
+        Ok((self.speaker_layout.channels() as usize, 2))
+    }
+
+    unsafe fn apply_ffi(
+        &self,
+        _params: &(),
+        input: &mut ffi::IPLAudioBuffer,
+        output: &mut ffi::IPLAudioBuffer,
+    ) {
+        let mut ipl_params = ffi::IPLVirtualSurroundEffectParams {
+            hrtf: self.hrtf.inner_raw(),
+        };
+        ffi::iplVirtualSurroundEffectApply(self.inner_raw(), &mut ipl_params, input, output);
+    }
+}
+
+impl Drop for VirtualSurroundEffect {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::iplVirtualSurroundEffectRelease(self.inner_mut());
+        }
+    }
+}
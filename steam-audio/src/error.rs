@@ -1,23 +1,183 @@
 use steam_audio_sys::ffi;
 
+use crate::simulation::simulation::ReflectionEffectType;
+use crate::simulation::static_mesh::MeshError;
+
 #[derive(Debug)]
 pub enum SteamAudioError {
     IPLError(ffi::IPLerror),
+    /// Audio was provided at a different sample rate than steam audio is running at.
+    SampleRateMismatch {
+        expected: u32,
+        got: u32,
+    },
+    /// Frame sizes have to be a power of two.
+    InvalidFrameSize(u32),
+    /// Sampling rate outside of [`AudioSettings::SAMPLING_RATES`].
+    ///
+    /// [`AudioSettings::SAMPLING_RATES`]: crate::hrtf::AudioSettings::SAMPLING_RATES
+    InvalidSamplingRate(u32),
+    /// Requested an API version the linked steam audio isn't compatible with.
+    IncompatibleVersion {
+        requested: u32,
+        linked: u32,
+    },
+    /// A buffer had a different number of channels than the operation needs.
+    ChannelMismatch {
+        expected: usize,
+        got: usize,
+    },
+    /// Buffers handed to the same operation had different frame sizes.
+    FrameSizeMismatch {
+        expected: usize,
+        got: usize,
+    },
+    /// Ambisonics order above what the effect was created with.
+    InvalidAmbisonicsOrder {
+        max: u8,
+        got: u8,
+    },
+    /// Reflection params simulated for a different kind of effect.
+    ReflectionTypeMismatch {
+        expected: ReflectionEffectType,
+        got: ReflectionEffectType,
+    },
+    /// Impulse response length doesn't match what the effect or mixer was created with.
+    IrSizeMismatch {
+        expected: u32,
+        got: u32,
+    },
+    /// Convolution or hybrid reflections without a simulated impulse response.
+    MissingImpulseResponse,
+    /// Mesh data steam audio can't use, e.g. an out of bounds vertex index.
+    InvalidMesh(MeshError),
+    /// Material data that can't be turned into a [`Material`](crate::simulation::material::Material).
+    InvalidMaterial(String),
+    Io(std::io::Error),
+    /// An asset couldn't be decoded.
+    Decode(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl SteamAudioError {
+    pub(crate) fn check_channels(expected: usize, got: usize) -> Result<(), Self> {
+        if expected != got {
+            return Err(Self::ChannelMismatch { expected, got });
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn check_frame_size(expected: usize, got: usize) -> Result<(), Self> {
+        if expected != got {
+            return Err(Self::FrameSizeMismatch { expected, got });
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn check_ambisonics_order(max: u8, got: u8) -> Result<(), Self> {
+        if got > max {
+            return Err(Self::InvalidAmbisonicsOrder { max, got });
+        }
+
+        Ok(())
+    }
 }
 
 impl std::fmt::Display for SteamAudioError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let description = match self {
-            Self::IPLError(error) => match error {
-                ffi::IPLerror::IPL_STATUS_SUCCESS => "ipl status success",
-                ffi::IPLerror::IPL_STATUS_FAILURE => "ipl status failure",
-                ffi::IPLerror::IPL_STATUS_OUTOFMEMORY => "ipl status out of memory",
-                ffi::IPLerror::IPL_STATUS_INITIALIZATION => "ipl status initialization: An error occurred while initializing an external dependency.",
+        match self {
+            Self::IPLError(error) => {
+                let description = match error {
+                    ffi::IPLerror::IPL_STATUS_SUCCESS => "ipl status success",
+                    ffi::IPLerror::IPL_STATUS_FAILURE => "ipl status failure",
+                    ffi::IPLerror::IPL_STATUS_OUTOFMEMORY => "ipl status out of memory",
+                    ffi::IPLerror::IPL_STATUS_INITIALIZATION => "ipl status initialization: An error occurred while initializing an external dependency.",
+                };
+
+                write!(f, "{}", description)
+            }
+            Self::SampleRateMismatch { expected, got } => write!(
+                f,
+                "sample rate mismatch: expected {} hz but got {} hz, resample the input first",
+                expected, got
+            ),
+            Self::InvalidFrameSize(frame_size) => {
+                write!(
+                    f,
+                    "invalid frame size {}, must be a power of two",
+                    frame_size
+                )
+            }
+            Self::InvalidSamplingRate(sampling_rate) => {
+                write!(f, "invalid sampling rate {} hz", sampling_rate)
             }
-        };
+            Self::IncompatibleVersion { requested, linked } => write!(
+                f,
+                "incompatible steam audio version: requested {}.{}.{} but linked {}.{}.{}",
+                requested >> 16,
+                (requested >> 8) & 0xff,
+                requested & 0xff,
+                linked >> 16,
+                (linked >> 8) & 0xff,
+                linked & 0xff
+            ),
+            Self::ChannelMismatch { expected, got } => write!(
+                f,
+                "channel mismatch: expected {} channels but got {}",
+                expected, got
+            ),
+            Self::FrameSizeMismatch { expected, got } => write!(
+                f,
+                "frame size mismatch: expected {} samples but got {}",
+                expected, got
+            ),
+            Self::InvalidAmbisonicsOrder { max, got } => write!(
+                f,
+                "invalid ambisonics order {}, the effect supports up to {}",
+                got, max
+            ),
+            Self::ReflectionTypeMismatch { expected, got } => write!(
+                f,
+                "reflection type mismatch: expected {:?} but got {:?}",
+                expected, got
+            ),
+            Self::IrSizeMismatch { expected, got } => write!(
+                f,
+                "impulse response size mismatch: expected {} samples but got {}",
+                expected, got
+            ),
+            Self::MissingImpulseResponse => write!(
+                f,
+                "missing impulse response, run the reflection simulation first"
+            ),
+            Self::InvalidMesh(error) => write!(f, "invalid mesh: {}", error),
+            Self::InvalidMaterial(reason) => write!(f, "invalid material: {}", reason),
+            Self::Io(error) => write!(f, "io error: {}", error),
+            Self::Decode(error) => write!(f, "decode error: {}", error),
+        }
+    }
+}
 
-        write!(f, "{}", description)
+impl std::error::Error for SteamAudioError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidMesh(error) => Some(error),
+            Self::Io(error) => Some(error),
+            Self::Decode(error) => Some(error.as_ref()),
+            _ => None,
+        }
     }
 }
 
-impl std::error::Error for SteamAudioError {}
+impl From<std::io::Error> for SteamAudioError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<lewton::VorbisError> for SteamAudioError {
+    fn from(error: lewton::VorbisError) -> Self {
+        Self::Decode(Box::new(error))
+    }
+}
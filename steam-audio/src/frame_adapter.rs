@@ -0,0 +1,271 @@
+use crate::prelude::*;
+
+/// Adapts the arbitrary block sizes of an audio host to the fixed frames steam
+/// audio works in.
+///
+/// Incoming samples are queued until a full frame is available, then handed to
+/// the processing closure. Output is delayed by [`FrameAdapter::latency`]
+/// samples so every block can be answered with the same number of samples.
+///
+/// ```ignore
+/// let mut adapter = FrameAdapter::from_audio_settings(&audio_settings, 1, 2);
+/// // In the host callback.
+/// adapter.process_interleaved(input, output, |input, output| {
+///     binaural_effect.apply_to_buffer(&params, input, output)
+/// })?;
+/// ```
+pub struct FrameAdapter {
+    input: DeinterleavedFrame,
+    output: DeinterleavedFrame,
+    // Samples per channel queued in `input`, which is also how many samples
+    // of `output` have been emitted.
+    position: usize,
+}
+
+impl FrameAdapter {
+    /// `frame_size` can't be 0.
+    pub fn new(
+        frame_size: usize,
+        input_channels: u16,
+        output_channels: u16,
+        sample_rate: u32,
+    ) -> Result<Self, SteamAudioError> {
+        if frame_size == 0 {
+            return Err(SteamAudioError::InvalidFrameSize(0));
+        }
+
+        Ok(Self::with_frame_size(
+            frame_size,
+            input_channels,
+            output_channels,
+            sample_rate,
+        ))
+    }
+
+    pub fn from_audio_settings(
+        audio_settings: &AudioSettings,
+        input_channels: u16,
+        output_channels: u16,
+    ) -> Self {
+        // Audio settings only hold power of two frame sizes.
+        Self::with_frame_size(
+            audio_settings.frame_size() as usize,
+            input_channels,
+            output_channels,
+            audio_settings.sampling_rate(),
+        )
+    }
+
+    fn with_frame_size(
+        frame_size: usize,
+        input_channels: u16,
+        output_channels: u16,
+        sample_rate: u32,
+    ) -> Self {
+        Self {
+            input: DeinterleavedFrame::new(frame_size, input_channels, sample_rate),
+            output: DeinterleavedFrame::new(frame_size, output_channels, sample_rate),
+            position: 0,
+        }
+    }
+
+    /// Delay added to the output, in samples per channel.
+    pub fn latency(&self) -> usize {
+        self.input.frame_size()
+    }
+
+    /// Delay added to the output.
+    pub fn latency_duration(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f64(self.latency() as f64 / self.input.sample_rate() as f64)
+    }
+
+    pub fn input_channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    pub fn output_channels(&self) -> u16 {
+        self.output.channels()
+    }
+
+    /// Drop anything queued and go back to outputting silence.
+    pub fn reset(&mut self) {
+        self.input.clear();
+        self.output.clear();
+        self.position = 0;
+    }
+
+    /// Process an interleaved block, `output` must hold as many samples per
+    /// channel as `input`.
+    pub fn process_interleaved<F>(
+        &mut self,
+        input: &[f32],
+        output: &mut [f32],
+        mut process: F,
+    ) -> Result<(), SteamAudioError>
+    where
+        F: FnMut(&mut DeinterleavedFrame, &mut DeinterleavedFrame) -> Result<(), SteamAudioError>,
+    {
+        let input_channels = self.input_channels() as usize;
+        let output_channels = self.output_channels() as usize;
+
+        let samples = input.len() / input_channels.max(1);
+        SteamAudioError::check_frame_size(samples * input_channels, input.len())?;
+        SteamAudioError::check_frame_size(samples * output_channels, output.len())?;
+
+        let mut offset = 0;
+        while offset < samples {
+            let count = (samples - offset).min(self.latency() - self.position);
+
+            for (channel_index, channel) in self.input.iter_channels_mut().enumerate() {
+                let queued = &mut channel[self.position..self.position + count];
+                for (index, sample) in queued.iter_mut().enumerate() {
+                    *sample = input[(offset + index) * input_channels + channel_index];
+                }
+            }
+
+            for (channel_index, channel) in self.output.iter_channels().enumerate() {
+                let ready = &channel[self.position..self.position + count];
+                for (index, sample) in ready.iter().enumerate() {
+                    output[(offset + index) * output_channels + channel_index] = *sample;
+                }
+            }
+
+            offset += count;
+            self.advance(count, &mut process)?;
+        }
+
+        Ok(())
+    }
+
+    /// Process a planar block, every channel in `input` and `output` must have
+    /// the same length.
+    pub fn process_planar<F>(
+        &mut self,
+        input: &[&[f32]],
+        output: &mut [&mut [f32]],
+        mut process: F,
+    ) -> Result<(), SteamAudioError>
+    where
+        F: FnMut(&mut DeinterleavedFrame, &mut DeinterleavedFrame) -> Result<(), SteamAudioError>,
+    {
+        SteamAudioError::check_channels(self.input_channels() as usize, input.len())?;
+        SteamAudioError::check_channels(self.output_channels() as usize, output.len())?;
+
+        let samples = input.first().map(|channel| channel.len()).unwrap_or(0);
+        let inputs = input.iter().map(|channel| channel.len());
+        let outputs = output.iter().map(|channel| channel.len());
+        for length in inputs.chain(outputs) {
+            SteamAudioError::check_frame_size(samples, length)?;
+        }
+
+        let mut offset = 0;
+        while offset < samples {
+            let count = (samples - offset).min(self.latency() - self.position);
+            let queued = self.position..self.position + count;
+            let block = offset..offset + count;
+
+            for (channel, input) in self.input.iter_channels_mut().zip(input) {
+                channel[queued.clone()].copy_from_slice(&input[block.clone()]);
+            }
+
+            for (channel, output) in self.output.iter_channels().zip(output.iter_mut()) {
+                output[block.clone()].copy_from_slice(&channel[queued.clone()]);
+            }
+
+            offset += count;
+            self.advance(count, &mut process)?;
+        }
+
+        Ok(())
+    }
+
+    fn advance<F>(&mut self, count: usize, process: &mut F) -> Result<(), SteamAudioError>
+    where
+        F: FnMut(&mut DeinterleavedFrame, &mut DeinterleavedFrame) -> Result<(), SteamAudioError>,
+    {
+        self.position += count;
+        if self.position == self.latency() {
+            self.position = 0;
+            process(&mut self.input, &mut self.output)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passthrough(
+        input: &mut DeinterleavedFrame,
+        output: &mut DeinterleavedFrame,
+    ) -> Result<(), SteamAudioError> {
+        for (output, input) in output.iter_channels_mut().zip(input.iter_channels()) {
+            output.copy_from_slice(input);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn interleaved_blocks_are_delayed_by_latency() {
+        let mut adapter = FrameAdapter::new(4, 2, 2, 48000).unwrap();
+        let input: Vec<f32> = (1..=40).map(|sample| sample as f32).collect();
+
+        let mut output = Vec::new();
+        let mut offset = 0;
+        for block in [3, 1, 7, 4, 5] {
+            let block = &input[offset * 2..(offset + block) * 2];
+            let mut processed = vec![0.0; block.len()];
+            adapter
+                .process_interleaved(block, &mut processed, passthrough)
+                .unwrap();
+            output.extend(processed);
+            offset += block.len() / 2;
+        }
+
+        let latency = adapter.latency() * 2;
+        assert!(output[..latency].iter().all(|sample| *sample == 0.0));
+        assert_eq!(output[latency..], input[..output.len() - latency]);
+    }
+
+    #[test]
+    fn planar_blocks_are_delayed_by_latency() {
+        let mut adapter = FrameAdapter::new(3, 1, 1, 48000).unwrap();
+        let input: Vec<f32> = (1..=10).map(|sample| sample as f32).collect();
+
+        let mut output = vec![0.0; input.len()];
+        for (input, output) in input.chunks(2).zip(output.chunks_mut(2)) {
+            adapter
+                .process_planar(&[input], &mut [output], passthrough)
+                .unwrap();
+        }
+
+        assert_eq!(output[..3], [0.0; 3]);
+        assert_eq!(output[3..], input[..7]);
+    }
+
+    #[test]
+    fn mismatched_blocks_are_errors() {
+        let mut adapter = FrameAdapter::new(4, 2, 1, 48000).unwrap();
+        assert!(matches!(
+            adapter.process_interleaved(&[0.0; 3], &mut [0.0; 1], passthrough),
+            Err(SteamAudioError::FrameSizeMismatch { .. })
+        ));
+        assert!(matches!(
+            adapter.process_planar(&[&[0.0; 2]], &mut [&mut [0.0; 2]], passthrough),
+            Err(SteamAudioError::ChannelMismatch {
+                expected: 2,
+                got: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn zero_frame_size_is_an_error() {
+        assert!(matches!(
+            FrameAdapter::new(0, 1, 1, 48000),
+            Err(SteamAudioError::InvalidFrameSize(0))
+        ));
+    }
+}
@@ -5,21 +5,27 @@ use steam_audio_sys::ffi;
 use crate::prelude::*;
 
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HRTFInterpolation {
     NearestNeighbor,
     Bilinear,
 }
 
-impl Into<ffi::IPLHRTFInterpolation> for HRTFInterpolation {
-    fn into(self) -> ffi::IPLHRTFInterpolation {
-        match self {
-            Self::NearestNeighbor => ffi::IPLHRTFInterpolation::IPL_HRTFINTERPOLATION_NEAREST,
-            Self::Bilinear => ffi::IPLHRTFInterpolation::IPL_HRTFINTERPOLATION_BILINEAR,
+impl From<HRTFInterpolation> for ffi::IPLHRTFInterpolation {
+    fn from(hrtf_interpolation: HRTFInterpolation) -> Self {
+        match hrtf_interpolation {
+            HRTFInterpolation::NearestNeighbor => {
+                ffi::IPLHRTFInterpolation::IPL_HRTFINTERPOLATION_NEAREST
+            }
+            HRTFInterpolation::Bilinear => {
+                ffi::IPLHRTFInterpolation::IPL_HRTFINTERPOLATION_BILINEAR
+            }
         }
     }
 }
 
 // TODO: Expose normType here.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HRTFSettings {
     Default {
         volume: f32,
@@ -37,8 +43,8 @@ impl Default for HRTFSettings {
     }
 }
 
-impl Into<ffi::IPLHRTFSettings> for &HRTFSettings {
-    fn into(self) -> ffi::IPLHRTFSettings {
+impl From<&HRTFSettings> for ffi::IPLHRTFSettings {
+    fn from(hrtf_settings: &HRTFSettings) -> Self {
         let mut settings = ffi::IPLHRTFSettings {
             type_: ffi::IPLHRTFType::IPL_HRTFTYPE_DEFAULT,
             sofaFileName: std::ptr::null_mut(),
@@ -48,7 +54,7 @@ impl Into<ffi::IPLHRTFSettings> for &HRTFSettings {
             normType: ffi::IPLHRTFNormType::IPL_HRTFNORMTYPE_NONE,
         };
 
-        match self {
+        match hrtf_settings {
             HRTFSettings::Default { volume } => {
                 settings.type_ = ffi::IPLHRTFType::IPL_HRTFTYPE_DEFAULT;
                 settings.volume = *volume;
@@ -67,11 +73,62 @@ impl Into<ffi::IPLHRTFSettings> for &HRTFSettings {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "UncheckedAudioSettings")
+)]
 pub struct AudioSettings {
     sampling_rate: u32,
     frame_size: u32,
 }
 
+// Deserialized settings go through the same validation as `AudioSettings::new`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedAudioSettings {
+    sampling_rate: u32,
+    frame_size: u32,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedAudioSettings> for AudioSettings {
+    type Error = SteamAudioError;
+    fn try_from(settings: UncheckedAudioSettings) -> Result<Self, Self::Error> {
+        Self::new(settings.sampling_rate, settings.frame_size)
+    }
+}
+
+/// Validated construction of [`AudioSettings`], starting from the defaults.
+///
+/// ```ignore
+/// let audio_settings = AudioSettings::builder()
+///     .sampling_rate(48000)
+///     .frame_size(512)
+///     .build()?;
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct AudioSettingsBuilder {
+    settings: AudioSettings,
+}
+
+impl AudioSettingsBuilder {
+    pub fn sampling_rate(mut self, sampling_rate: u32) -> Self {
+        self.settings.sampling_rate = sampling_rate;
+        self
+    }
+
+    pub fn frame_size(mut self, frame_size: u32) -> Self {
+        self.settings.frame_size = frame_size;
+        self
+    }
+
+    pub fn build(self) -> Result<AudioSettings, SteamAudioError> {
+        AudioSettings::new(self.settings.sampling_rate, self.settings.frame_size)
+    }
+}
+
 impl Default for AudioSettings {
     fn default() -> Self {
         Self {
@@ -81,16 +138,41 @@ impl Default for AudioSettings {
     }
 }
 
-impl Into<ffi::IPLAudioSettings> for &AudioSettings {
-    fn into(self) -> ffi::IPLAudioSettings {
+impl From<&AudioSettings> for ffi::IPLAudioSettings {
+    fn from(audio_settings: &AudioSettings) -> Self {
         ffi::IPLAudioSettings {
-            samplingRate: self.sampling_rate as i32,
-            frameSize: self.frame_size as i32,
+            samplingRate: audio_settings.sampling_rate as i32,
+            frameSize: audio_settings.frame_size as i32,
         }
     }
 }
 
 impl AudioSettings {
+    /// Lowest and highest sampling rates accepted, in hz.
+    pub const SAMPLING_RATES: std::ops::RangeInclusive<u32> = 8000..=192000;
+
+    /// `frame_size` must be a power of two and `sampling_rate` within [`Self::SAMPLING_RATES`].
+    pub fn new(sampling_rate: u32, frame_size: u32) -> Result<Self, SteamAudioError> {
+        if !Self::SAMPLING_RATES.contains(&sampling_rate) {
+            return Err(SteamAudioError::InvalidSamplingRate(sampling_rate));
+        }
+
+        if !frame_size.is_power_of_two() {
+            return Err(SteamAudioError::InvalidFrameSize(frame_size));
+        }
+
+        Ok(Self {
+            sampling_rate,
+            frame_size,
+        })
+    }
+
+    pub fn builder() -> AudioSettingsBuilder {
+        AudioSettingsBuilder {
+            settings: Self::default(),
+        }
+    }
+
     pub fn sampling_rate(&self) -> u32 {
         self.sampling_rate
     }
@@ -98,6 +180,18 @@ impl AudioSettings {
     pub fn frame_size(&self) -> u32 {
         self.frame_size
     }
+
+    /// Error if audio at `sample_rate` would need resampling before it can be processed.
+    pub fn check_sample_rate(&self, sample_rate: u32) -> Result<(), SteamAudioError> {
+        if sample_rate == self.sampling_rate {
+            Ok(())
+        } else {
+            Err(SteamAudioError::SampleRateMismatch {
+                expected: self.sampling_rate,
+                got: sample_rate,
+            })
+        }
+    }
 }
 
 pub struct HRTF {
@@ -135,6 +229,17 @@ impl HRTF {
             }
         }
     }
+
+    pub fn retain(&self) -> HRTF {
+        unsafe {
+            let new_hrtf = ffi::iplHRTFRetain(self.inner_raw());
+            HRTF {
+                inner: new_hrtf,
+                hrtf_settings: self.hrtf_settings,
+                audio_settings: self.audio_settings,
+            }
+        }
+    }
 }
 
 impl crate::SteamAudioObject for HRTF {
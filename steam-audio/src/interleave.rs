@@ -1,24 +1,40 @@
-/// Interleave de-interleaved PCM data.
+use steam_audio_sys::ffi;
+
+use crate::audio_buffer::RawBuffer;
+use crate::prelude::*;
+
+/// Interleave de-interleaved PCM data, every channel must be the same length.
 ///
 /// 111222333 -> 123123123
-pub fn interleave(pcm: Vec<Vec<f32>>) -> Vec<f32> {
-    let mut output = Vec::new();
-    let length = match pcm.get(0) {
-        Some(first_channel) => first_channel.len(),
-        None => return output,
-    };
+pub fn interleave<C: AsRef<[f32]>>(
+    context: &Context,
+    pcm: &[C],
+) -> Result<Vec<f32>, SteamAudioError> {
+    let channels: Vec<&[f32]> = pcm.iter().map(AsRef::as_ref).collect();
+    let mut buffer = RawBuffer::from_slices(&channels)?;
+    let frame_size = channels.first().map_or(0, |channel| channel.len());
 
-    for index in 0..length {
-        for channel in pcm.iter() {
-            output.push(channel[index]);
+    let mut output = vec![0.0; channels.len() * frame_size];
+    if !output.is_empty() {
+        unsafe {
+            ffi::iplAudioBufferInterleave(
+                context.inner_raw(),
+                &mut buffer.as_ffi(),
+                output.as_mut_ptr(),
+            );
         }
     }
 
-    output
+    Ok(output)
 }
 
-pub fn extend_deinterleaved(pcm: &mut Vec<Vec<f32>>, extend_with: &Vec<Vec<f32>>) {
-    for (channel, pcm) in extend_with.iter().zip(pcm.iter_mut()) {
-        pcm.extend(channel);
+/// Append each channel of `extend_with` onto the matching channel of `pcm`.
+pub fn extend_deinterleaved<I, C>(pcm: &mut [Vec<f32>], extend_with: I)
+where
+    I: IntoIterator<Item = C>,
+    C: AsRef<[f32]>,
+{
+    for (channel, pcm) in extend_with.into_iter().zip(pcm.iter_mut()) {
+        pcm.extend_from_slice(channel.as_ref());
     }
 }
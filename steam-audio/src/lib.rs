@@ -1,13 +1,22 @@
+pub mod allocator;
 pub mod audio_buffer;
 pub mod context;
 pub mod device;
 pub mod effect;
 pub mod error;
+pub mod frame_adapter;
 pub mod hrtf;
 pub mod interleave;
+pub mod logging;
 pub mod orientation;
 pub mod raw;
+pub mod resample;
+#[cfg(feature = "serde")]
+mod serde_flags;
 pub mod simulation;
+pub mod spatial;
+pub mod speaker_layout;
+pub mod stream;
 
 pub trait SteamAudioObject: Send + Sync {
     type Object: Clone;
@@ -20,36 +29,59 @@ pub trait SteamAudioObject: Send + Sync {
     fn inner_mut(&mut self) -> *mut Self::Object;
 }
 
-//pub use effect::ambisonics::ambisonic_order_channels;
+pub use effect::ambisonics::ambisonic_order_channels;
 pub use interleave::{extend_deinterleaved, interleave};
 pub use orientation::Orientation;
 pub use raw::{read_ogg, write_file};
 pub use simulation::material::materials;
+pub use speaker_layout::SpeakerLayout;
 
 pub mod prelude {
-    pub use crate::audio_buffer::DeinterleavedFrame;
-    pub use crate::context::{Context, ContextSettings};
+    pub use crate::allocator::{AllocationStats, Allocator};
+    pub use crate::audio_buffer::{DeinterleavedFrame, Filled};
+    pub use crate::context::{
+        ConcreteLevel, Context, ContextSettings, ContextSettingsBuilder, SimdLevel,
+    };
     pub use crate::effect::{
-        /*
         ambisonics::{
-            decode::{AmbisonicsDecode, AmbisonicsDecodeParams},
+            binaural::{AmbisonicsBinaural, AmbisonicsBinauralParams},
+            decode::{AmbisonicsDecode, AmbisonicsDecodeParams, AmbisonicsDecodeSettings},
             encode::{AmbisonicsEncode, AmbisonicsEncodeParams},
+            panning::{AmbisonicsPanning, AmbisonicsPanningParams, AmbisonicsPanningSettings},
+            rotation::{AmbisonicsRotation, AmbisonicsRotationParams},
+            AmbisonicsType,
         },
-        */
         binaural::{BinauralEffect, BinauralParams},
         direct::{DirectEffect, DirectEffectFlags, DirectEffectParams, DirectSimulationFlags},
+        panning::{PanningEffect, PanningParams},
+        reflection::{
+            ReflectionEffect, ReflectionEffectParams, ReflectionEffectSettings, ReflectionMix,
+            ReflectionMixer,
+        },
+        virtual_surround::VirtualSurroundEffect,
+        Effect,
     };
     pub use crate::error::SteamAudioError;
-    pub use crate::hrtf::{AudioSettings, HRTFInterpolation, HRTFSettings, HRTF};
+    pub use crate::frame_adapter::FrameAdapter;
+    pub use crate::hrtf::{
+        AudioSettings, AudioSettingsBuilder, HRTFInterpolation, HRTFSettings, HRTF,
+    };
+    pub use crate::logging::{LogLevel, LogSink};
+    pub use crate::resample::Resampler;
     pub use crate::simulation::{
-        material::Material,
+        instanced_mesh::{InstancedMesh, Transform},
+        material::{Material, MaterialLibrary},
         scene::{Scene, SceneSettings},
+        shapes::Wall,
         simulation::{SimulationFlags, SimulationSettings, SimulationSharedInputs, Simulator},
         source::{
             DistanceAttenuationCallback, DistanceAttenuationModel, SimulationInputs, Source,
             SourceSettings,
         },
-        static_mesh::{StaticMesh, StaticMeshSettings},
+        static_mesh::{MeshError, StaticMesh, StaticMeshSettings},
     };
+    pub use crate::spatial::{Spatial, SpatialHandle};
+    pub use crate::speaker_layout::SpeakerLayout;
+    pub use crate::stream::{FrameStream, ProcessFrame};
     pub use crate::SteamAudioObject;
 }
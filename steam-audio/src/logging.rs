@@ -0,0 +1,209 @@
+use std::ffi::CStr;
+use std::sync::{Arc, RwLock};
+
+use steam_audio_sys::ffi;
+
+/// Severity of a message logged by steam audio, ordered from least to most severe.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Debug,
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+impl From<ffi::IPLLogLevel> for LogLevel {
+    fn from(level: ffi::IPLLogLevel) -> Self {
+        match level {
+            ffi::IPLLogLevel::IPL_LOGLEVEL_DEBUG => Self::Debug,
+            ffi::IPLLogLevel::IPL_LOGLEVEL_INFO => Self::Info,
+            ffi::IPLLogLevel::IPL_LOGLEVEL_WARNING => Self::Warning,
+            ffi::IPLLogLevel::IPL_LOGLEVEL_ERROR => Self::Error,
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<LogLevel> for ::log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Debug => Self::Debug,
+            LogLevel::Info => Self::Info,
+            LogLevel::Warning => Self::Warn,
+            LogLevel::Error => Self::Error,
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl From<LogLevel> for ::tracing::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Debug => Self::DEBUG,
+            LogLevel::Info => Self::INFO,
+            LogLevel::Warning => Self::WARN,
+            LogLevel::Error => Self::ERROR,
+        }
+    }
+}
+
+/// User supplied destination for steam audio log messages.
+///
+/// Called from whatever thread steam audio logs on, including the audio thread.
+#[derive(Clone)]
+pub struct LogSink(Arc<SinkFn>);
+
+type SinkFn = dyn Fn(LogLevel, &str) + Send + Sync;
+
+impl LogSink {
+    pub fn new<F>(sink: F) -> Self
+    where
+        F: Fn(LogLevel, &str) + Send + Sync + 'static,
+    {
+        Self(Arc::new(sink))
+    }
+
+    pub fn log(&self, level: LogLevel, message: &str) {
+        (self.0)(level, message)
+    }
+}
+
+impl std::fmt::Debug for LogSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogSink").finish_non_exhaustive()
+    }
+}
+
+struct Logger {
+    sink: Option<LogSink>,
+    min_level: LogLevel,
+}
+
+impl Logger {
+    fn dispatch(&self, level: LogLevel, message: &str) {
+        if level < self.min_level {
+            return;
+        }
+
+        if let Some(sink) = &self.sink {
+            sink.log(level, message);
+        } else {
+            #[cfg(feature = "log")]
+            ::log::log!(target: "steam_audio", level.into(), "{}", message);
+
+            #[cfg(feature = "tracing")]
+            match level {
+                LogLevel::Debug => ::tracing::debug!(target: "steam_audio", "{}", message),
+                LogLevel::Info => ::tracing::info!(target: "steam_audio", "{}", message),
+                LogLevel::Warning => ::tracing::warn!(target: "steam_audio", "{}", message),
+                LogLevel::Error => ::tracing::error!(target: "steam_audio", "{}", message),
+            }
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `message` has to be null or a nul terminated string.
+    unsafe fn dispatch_raw(&self, level: ffi::IPLLogLevel, message: *const ::std::os::raw::c_char) {
+        if message.is_null() {
+            return;
+        }
+
+        let message = CStr::from_ptr(message).to_string_lossy();
+        self.dispatch(level.into(), message.trim_end());
+    }
+}
+
+// Steam audio's log callback has no user data pointer, so the sink and level
+// are shared by the whole process, the last context to set them wins.
+static LOGGER: RwLock<Logger> = RwLock::new(Logger {
+    sink: None,
+    min_level: LogLevel::Info,
+});
+
+/// Replace whichever of the sink and level are given, leaving the other as is.
+pub(crate) fn install(sink: Option<LogSink>, min_level: Option<LogLevel>) {
+    if sink.is_none() && min_level.is_none() {
+        return;
+    }
+
+    let mut logger = LOGGER
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(sink) = sink {
+        logger.sink = Some(sink);
+    }
+    if let Some(min_level) = min_level {
+        logger.min_level = min_level;
+    }
+}
+
+pub(crate) fn dispatch(level: LogLevel, message: &str) {
+    LOGGER
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .dispatch(level, message);
+}
+
+pub(crate) unsafe extern "C" fn log_callback(
+    level: ffi::IPLLogLevel,
+    message: *const ::std::os::raw::c_char,
+) {
+    // Unwinding into steam audio is undefined behaviour, a panicking sink is dropped on the floor.
+    let _ = std::panic::catch_unwind(|| {
+        LOGGER
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .dispatch_raw(level, message)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_order_by_severity() {
+        assert!(LogLevel::Debug < LogLevel::Info);
+        assert!(LogLevel::Info < LogLevel::Warning);
+        assert!(LogLevel::Warning < LogLevel::Error);
+        assert_eq!(
+            LogLevel::from(ffi::IPLLogLevel::IPL_LOGLEVEL_WARNING),
+            LogLevel::Warning
+        );
+    }
+
+    #[test]
+    fn sink_receives_filtered_messages() {
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = {
+            let received = received.clone();
+            LogSink::new(move |level, message: &str| {
+                received.lock().unwrap().push((level, message.to_owned()))
+            })
+        };
+        // A local logger, the global one is shared with every test creating a context.
+        let logger = Logger {
+            sink: Some(sink),
+            min_level: LogLevel::Warning,
+        };
+
+        unsafe {
+            logger.dispatch_raw(
+                ffi::IPLLogLevel::IPL_LOGLEVEL_INFO,
+                c"ignored".as_ptr() as _,
+            );
+            logger.dispatch_raw(
+                ffi::IPLLogLevel::IPL_LOGLEVEL_ERROR,
+                c"bad \xff\n".as_ptr() as _,
+            );
+            logger.dispatch_raw(ffi::IPLLogLevel::IPL_LOGLEVEL_ERROR, std::ptr::null());
+        }
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            [(LogLevel::Error, "bad \u{fffd}".to_owned())]
+        );
+    }
+}
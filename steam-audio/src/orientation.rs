@@ -1,6 +1,9 @@
 use steam_audio_sys::ffi;
 
+use crate::prelude::*;
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Orientation {
     pub right: [f32; 3],
     pub up: [f32; 3],
@@ -19,13 +22,248 @@ impl Default for Orientation {
     }
 }
 
-impl Into<ffi::IPLCoordinateSpace3> for Orientation {
-    fn into(self) -> ffi::IPLCoordinateSpace3 {
+impl From<Orientation> for ffi::IPLCoordinateSpace3 {
+    fn from(orientation: Orientation) -> Self {
         ffi::IPLCoordinateSpace3 {
-            right: self.right.into(),
-            up: self.up.into(),
-            ahead: self.ahead.into(),
-            origin: self.origin.into(),
+            right: orientation.right.into(),
+            up: orientation.up.into(),
+            ahead: orientation.ahead.into(),
+            origin: orientation.origin.into(),
+        }
+    }
+}
+
+impl Orientation {
+    /// Unit vector pointing from this listener towards `source_position`, in the
+    /// listener's own coordinate space.
+    ///
+    /// Unlike `(source - listener).normalize()` this takes the rotation of the
+    /// listener into account, which is what [`BinauralParams::direction`] expects.
+    pub fn relative_direction(
+        &self,
+        context: &Context,
+        source_position: impl Into<[f32; 3]>,
+    ) -> [f32; 3] {
+        unsafe {
+            ffi::iplCalculateRelativeDirection(
+                context.inner_raw(),
+                source_position.into().into(),
+                self.origin.into(),
+                self.ahead.into(),
+                self.up.into(),
+            )
+        }
+        .into()
+    }
+
+    /// Orientation of something rotated by `rotation`, a unit quaternion as
+    /// `[x, y, z, w]`, and placed at `origin`.
+    ///
+    /// Unrotated it faces -z with y up, like [`Orientation::default`].
+    pub fn from_rotation(rotation: [f32; 4], origin: impl Into<[f32; 3]>) -> Self {
+        let [x, y, z, w] = rotation;
+        let right = [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y + w * z),
+            2.0 * (x * z - w * y),
+        ];
+        let up = [
+            2.0 * (x * y - w * z),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z + w * x),
+        ];
+        let back = [
+            2.0 * (x * z + w * y),
+            2.0 * (y * z - w * x),
+            1.0 - 2.0 * (x * x + y * y),
+        ];
+        Self::from_axes(right, up, back, origin.into())
+    }
+
+    /// Orientation from the columns of an affine transform, any scale is removed.
+    pub fn from_affine(
+        x_axis: [f32; 3],
+        y_axis: [f32; 3],
+        z_axis: [f32; 3],
+        translation: impl Into<[f32; 3]>,
+    ) -> Self {
+        Self::from_axes(x_axis, y_axis, z_axis, translation.into())
+    }
+
+    fn from_axes(right: [f32; 3], up: [f32; 3], back: [f32; 3], origin: [f32; 3]) -> Self {
+        let normalize = |axis: [f32; 3]| {
+            let length = axis.iter().map(|value| value * value).sum::<f32>().sqrt();
+            if length > 0.0 {
+                axis.map(|value| value / length)
+            } else {
+                axis
+            }
+        };
+
+        Self {
+            right: normalize(right),
+            up: normalize(up),
+            ahead: normalize(back.map(|value| -value)),
+            origin,
+        }
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Affine3A> for Orientation {
+    fn from(transform: glam::Affine3A) -> Self {
+        let matrix = transform.matrix3;
+        Self::from_affine(
+            matrix.x_axis.into(),
+            matrix.y_axis.into(),
+            matrix.z_axis.into(),
+            transform.translation,
+        )
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Mat4> for Orientation {
+    fn from(transform: glam::Mat4) -> Self {
+        Self::from_affine(
+            transform.x_axis.truncate().into(),
+            transform.y_axis.truncate().into(),
+            transform.z_axis.truncate().into(),
+            transform.w_axis.truncate(),
+        )
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<(glam::Quat, glam::Vec3)> for Orientation {
+    fn from((rotation, origin): (glam::Quat, glam::Vec3)) -> Self {
+        Self::from_rotation(rotation.to_array(), origin)
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<mint::ColumnMatrix4<f32>> for Orientation {
+    fn from(transform: mint::ColumnMatrix4<f32>) -> Self {
+        let truncate = |column: mint::Vector4<f32>| [column.x, column.y, column.z];
+        Self::from_affine(
+            truncate(transform.x),
+            truncate(transform.y),
+            truncate(transform.z),
+            truncate(transform.w),
+        )
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<(mint::Quaternion<f32>, mint::Vector3<f32>)> for Orientation {
+    fn from((rotation, origin): (mint::Quaternion<f32>, mint::Vector3<f32>)) -> Self {
+        let mint::Vector3 { x, y, z } = rotation.v;
+        Self::from_rotation([x, y, z, rotation.s], origin)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Isometry3<f32>> for Orientation {
+    fn from(transform: nalgebra::Isometry3<f32>) -> Self {
+        Self::from((transform.rotation, transform.translation.vector))
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Affine3<f32>> for Orientation {
+    fn from(transform: nalgebra::Affine3<f32>) -> Self {
+        let matrix = transform.matrix();
+        let column = |index: usize| {
+            let column = matrix.column(index);
+            [column[0], column[1], column[2]]
+        };
+        Self::from_affine(column(0), column(1), column(2), column(3))
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<(nalgebra::UnitQuaternion<f32>, nalgebra::Vector3<f32>)> for Orientation {
+    fn from((rotation, origin): (nalgebra::UnitQuaternion<f32>, nalgebra::Vector3<f32>)) -> Self {
+        let nalgebra::Quaternion { coords } = rotation.into_inner();
+        Self::from_rotation([coords.x, coords.y, coords.z, coords.w], origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        for (a, b) in a.iter().zip(&b) {
+            assert!((a - b).abs() < 1e-5, "{:?} != {:?}", a, b);
         }
     }
+
+    #[test]
+    fn identity_rotation_is_default() {
+        let orientation = Orientation::from_rotation([0.0, 0.0, 0.0, 1.0], [1.0, 2.0, 3.0]);
+        let default = Orientation::default();
+        assert_close(orientation.right, default.right);
+        assert_close(orientation.up, default.up);
+        assert_close(orientation.ahead, default.ahead);
+        assert_eq!(orientation.origin, [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn yaw_turns_ahead() {
+        // Quarter turn to the left around y.
+        let half = std::f32::consts::FRAC_PI_4;
+        let orientation = Orientation::from_rotation([0.0, half.sin(), 0.0, half.cos()], [0.0; 3]);
+        assert_close(orientation.ahead, [-1.0, 0.0, 0.0]);
+        assert_close(orientation.right, [0.0, 0.0, -1.0]);
+        assert_close(orientation.up, [0.0, 1.0, 0.0]);
+
+        let scaled =
+            Orientation::from_affine([0.0, 0.0, -2.0], [0.0, 2.0, 0.0], [2.0, 0.0, 0.0], [0.0; 3]);
+        assert_close(scaled.ahead, orientation.ahead);
+        assert_close(scaled.right, orientation.right);
+    }
+
+    #[cfg(all(feature = "glam", feature = "mint", feature = "nalgebra"))]
+    #[test]
+    fn math_libraries_agree() {
+        let rotation = glam::Quat::from_euler(glam::EulerRot::YXZ, 0.3, -0.7, 1.1);
+        let origin = glam::Vec3::new(1.0, -2.0, 3.0);
+        let expected = Orientation::from((rotation, origin));
+
+        let from_affine =
+            Orientation::from(glam::Affine3A::from_rotation_translation(rotation, origin));
+        let from_mint = Orientation::from((
+            mint::Quaternion {
+                v: mint::Vector3 {
+                    x: rotation.x,
+                    y: rotation.y,
+                    z: rotation.z,
+                },
+                s: rotation.w,
+            },
+            mint::Vector3 {
+                x: origin.x,
+                y: origin.y,
+                z: origin.z,
+            },
+        ));
+        let nalgebra_rotation = nalgebra::UnitQuaternion::from_quaternion(
+            nalgebra::Quaternion::new(rotation.w, rotation.x, rotation.y, rotation.z),
+        );
+        let from_nalgebra = Orientation::from(nalgebra::Isometry3::from_parts(
+            nalgebra::Translation3::new(origin.x, origin.y, origin.z),
+            nalgebra_rotation,
+        ));
+
+        for orientation in [from_affine, from_mint, from_nalgebra] {
+            assert_close(orientation.right, expected.right);
+            assert_close(orientation.up, expected.up);
+            assert_close(orientation.ahead, expected.ahead);
+            assert_close(orientation.origin, expected.origin);
+        }
+
+        // Steam audio's ahead is the -z axis of the transform.
+        assert_close(expected.ahead, (rotation * -glam::Vec3::Z).into());
+    }
 }
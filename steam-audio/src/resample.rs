@@ -0,0 +1,342 @@
+use std::f64::consts::PI;
+
+use rodio::cpal::Sample as _;
+use rodio::Sample;
+
+use crate::prelude::*;
+
+// Zero crossings of the sinc kernel on each side of the output sample.
+const HALF_TAPS: f64 = 16.0;
+// Kernel table entries per input sample, values in between are interpolated.
+const PHASES: f64 = 256.0;
+// Fraction of the lower Nyquist frequency left untouched by the lowpass.
+const BANDWIDTH: f64 = 0.95;
+// How many spent input frames to keep around before shifting them out.
+const DRAIN_THRESHOLD: usize = 1024;
+
+/// Windowed sinc resampler, converts a rodio source to another sample rate.
+///
+/// Steam audio expects its input at [`AudioSettings::sampling_rate`], feeding
+/// it anything else plays back at the wrong pitch with HRTFs for the wrong rate.
+///
+/// The channel count and sample rate of `source` must not change while playing.
+pub struct Resampler<S> {
+    source: S,
+    channels: usize,
+    from: u32,
+    to: u32,
+    // Input frames per output frame.
+    ratio: f64,
+    // Kernel reach in input frames on each side.
+    half_width: f64,
+    kernel: Vec<f32>,
+
+    // Interleaved input frames, `history[0]` is input frame `base`.
+    history: Vec<f32>,
+    base: i64,
+    exhausted: bool,
+
+    output_frame: u64,
+    output: Vec<f32>,
+    output_channel: usize,
+}
+
+impl<S> Resampler<S>
+where
+    S: rodio::Source + Iterator,
+    S::Item: Sample,
+{
+    /// Fails if `sample_rate` is 0, or `source` reports 0 hz or no channels.
+    pub fn new(source: S, sample_rate: u32) -> Result<Self, SteamAudioError> {
+        let channels = source.channels() as usize;
+        let from = source.sample_rate();
+        for rate in [sample_rate, from] {
+            if rate == 0 {
+                return Err(SteamAudioError::InvalidSamplingRate(rate));
+            }
+        }
+        if channels == 0 {
+            return Err(SteamAudioError::ChannelMismatch {
+                expected: 1,
+                got: 0,
+            });
+        }
+
+        let ratio = from as f64 / sample_rate as f64;
+
+        // Lowpass at the lower of the two Nyquist frequencies, relative to the input rate.
+        let cutoff = BANDWIDTH * (1.0 / ratio).min(1.0);
+        let half_width = HALF_TAPS / cutoff;
+
+        let kernel = (0..(half_width * PHASES).ceil() as usize + 2)
+            .map(|index| {
+                let x = index as f64 / PHASES;
+                if x >= half_width {
+                    return 0.0;
+                }
+
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (PI * cutoff * x).sin() / (PI * cutoff * x)
+                };
+
+                // Blackman window over [-half_width, half_width].
+                let phase = PI * (x / half_width + 1.0);
+                let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+
+                (cutoff * sinc * window) as f32
+            })
+            .collect();
+
+        Ok(Self {
+            source,
+            channels,
+            from,
+            to: sample_rate,
+            ratio,
+            half_width,
+            kernel,
+            history: Vec::new(),
+            base: 0,
+            exhausted: false,
+            output_frame: 0,
+            output: vec![0.0; channels],
+            output_channel: channels,
+        })
+    }
+
+    /// Resample `source` to the rate steam audio is running at.
+    pub fn for_audio_settings(
+        source: S,
+        audio_settings: &AudioSettings,
+    ) -> Result<Self, SteamAudioError> {
+        Self::new(source, audio_settings.sampling_rate())
+    }
+
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+
+    fn kernel(&self, offset: f64) -> f32 {
+        let position = offset.abs() * PHASES;
+        let index = position as usize;
+        if index + 1 >= self.kernel.len() {
+            return 0.0;
+        }
+
+        let fraction = (position - index as f64) as f32;
+        self.kernel[index] * (1.0 - fraction) + self.kernel[index + 1] * fraction
+    }
+
+    fn buffered_end(&self) -> i64 {
+        self.base + (self.history.len() / self.channels) as i64
+    }
+
+    fn buffer_until(&mut self, frame: i64) {
+        while !self.exhausted && self.buffered_end() <= frame {
+            for channel in 0..self.channels {
+                match self.source.next() {
+                    Some(sample) => self.history.push(sample.to_f32()),
+                    None => {
+                        // Silence whatever is missing from the last frame.
+                        if channel > 0 {
+                            self.history
+                                .extend(std::iter::repeat_n(0.0, self.channels - channel));
+                        }
+
+                        self.exhausted = true;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    fn next_frame(&mut self) -> bool {
+        let time = self.output_frame as f64 * self.ratio;
+        let reach = self.half_width.ceil() as i64;
+        let center = time.floor() as i64;
+        let (first, last) = (center - reach + 1, center + reach);
+
+        self.buffer_until(last);
+        if self.exhausted && time >= self.buffered_end() as f64 {
+            return false;
+        }
+
+        self.output.fill(0.0);
+        for frame in first.max(self.base)..=last.min(self.buffered_end() - 1) {
+            let weight = self.kernel(time - frame as f64);
+            let start = (frame - self.base) as usize * self.channels;
+            let samples = &self.history[start..start + self.channels];
+            for (output, sample) in self.output.iter_mut().zip(samples) {
+                *output += sample * weight;
+            }
+        }
+
+        // Frames before `first` are never needed again.
+        let spent = (first - self.base).max(0) as usize;
+        if spent > DRAIN_THRESHOLD {
+            self.history.drain(..spent * self.channels);
+            self.base += spent as i64;
+        }
+
+        self.output_frame += 1;
+        self.output_channel = 0;
+        true
+    }
+}
+
+impl<S> Iterator for Resampler<S>
+where
+    S: rodio::Source + Iterator,
+    S::Item: Sample,
+{
+    type Item = f32;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.from == self.to {
+            return self.source.next().map(|sample| sample.to_f32());
+        }
+
+        if self.output_channel >= self.channels && !self.next_frame() {
+            return None;
+        }
+
+        let sample = self.output[self.output_channel];
+        self.output_channel += 1;
+        Some(sample)
+    }
+}
+
+impl<S> rodio::Source for Resampler<S>
+where
+    S: rodio::Source + Iterator,
+    S::Item: Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels as u16
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.to
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        self.source.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    fn sine(frequency: f64, sample_rate: u32, samples: usize) -> Vec<f32> {
+        (0..samples)
+            .map(|index| (2.0 * PI * frequency * index as f64 / sample_rate as f64).sin() as f32)
+            .collect()
+    }
+
+    #[test]
+    fn matching_rates_pass_through() {
+        let samples = sine(440.0, 48000, 1000);
+        let resampled: Vec<f32> =
+            Resampler::new(SamplesBuffer::new(1, 48000, samples.clone()), 48000)
+                .unwrap()
+                .collect();
+        assert_eq!(resampled, samples);
+    }
+
+    #[test]
+    fn converts_sine_between_rates() {
+        let input = sine(440.0, 48000, 48000);
+        let resampler = Resampler::new(SamplesBuffer::new(1, 48000, input), 44100).unwrap();
+        assert_eq!(rodio::Source::sample_rate(&resampler), 44100);
+
+        let resampled: Vec<f32> = resampler.collect();
+        assert!((resampled.len() as i64 - 44100).abs() <= 1);
+
+        // Skip the edges where the kernel runs past the input.
+        let expected = sine(440.0, 44100, 44100);
+        for (sample, expected) in resampled[100..44000].iter().zip(&expected[100..44000]) {
+            assert!(
+                (sample - expected).abs() < 1e-2,
+                "{} != {}",
+                sample,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_channels_interleaved() {
+        let input: Vec<f32> = (0..2000).map(|index| (index % 2) as f32).collect();
+        let resampled: Vec<f32> = Resampler::new(SamplesBuffer::new(2, 22050, input), 44100)
+            .unwrap()
+            .collect();
+
+        for frame in resampled[200..3800].chunks(2) {
+            assert!(frame[0].abs() < 1e-2);
+            assert!((frame[1] - 1.0).abs() < 1e-2);
+        }
+    }
+
+    // Reports any layout, rodio's own sources refuse zeros.
+    struct Silent {
+        channels: u16,
+        sample_rate: u32,
+    }
+
+    impl Iterator for Silent {
+        type Item = f32;
+        fn next(&mut self) -> Option<f32> {
+            None
+        }
+    }
+
+    impl rodio::Source for Silent {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            self.channels
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.sample_rate
+        }
+
+        fn total_duration(&self) -> Option<std::time::Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn rejects_zero_rates_and_channels() {
+        let silent = |channels, sample_rate| Silent {
+            channels,
+            sample_rate,
+        };
+        assert!(matches!(
+            Resampler::new(silent(1, 48000), 0),
+            Err(SteamAudioError::InvalidSamplingRate(0))
+        ));
+        assert!(matches!(
+            Resampler::new(silent(1, 0), 48000),
+            Err(SteamAudioError::InvalidSamplingRate(0))
+        ));
+        assert!(matches!(
+            Resampler::new(silent(0, 48000), 44100),
+            Err(SteamAudioError::ChannelMismatch {
+                expected: 1,
+                got: 0
+            })
+        ));
+        assert!(Resampler::new(silent(1, 48000), 44100).is_ok());
+    }
+}
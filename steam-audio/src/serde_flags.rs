@@ -0,0 +1,96 @@
+/// Serialize a bitflags type as a list of flag names, e.g. `["DIRECT", "PATHING"]`.
+///
+/// Only the listed flags round trip, composite flags like `ALL` are left out so
+/// they serialize as their parts.
+macro_rules! serde_flags {
+    ($flags:ty { $($name:ident),* $(,)? }) => {
+        impl serde::Serialize for $flags {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeSeq;
+
+                let names = [$((stringify!($name), <$flags>::$name)),*];
+                let contained: Vec<_> = names
+                    .iter()
+                    .filter(|(_, flag)| self.contains(*flag))
+                    .collect();
+
+                let mut seq = serializer.serialize_seq(Some(contained.len()))?;
+                for (name, _) in contained {
+                    seq.serialize_element(name)?;
+                }
+                seq.end()
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $flags {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                const NAMES: &[&str] = &[$(stringify!($name)),*];
+
+                let names = Vec::<String>::deserialize(deserializer)?;
+                names.iter().try_fold(Self::empty(), |flags, name| match name.as_str() {
+                    $(stringify!($name) => Ok(flags | <$flags>::$name),)*
+                    _ => Err(serde::de::Error::unknown_variant(name, NAMES)),
+                })
+            }
+        }
+    };
+}
+
+pub(crate) use serde_flags;
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::Orientation;
+
+    #[test]
+    fn flags_serialize_by_name() {
+        let flags = SimulationFlags::DIRECT | SimulationFlags::PATHING;
+        let json = serde_json::to_string(&flags).unwrap();
+        assert_eq!(json, r#"["DIRECT","PATHING"]"#);
+        assert_eq!(
+            serde_json::from_str::<SimulationFlags>(&json).unwrap(),
+            flags
+        );
+
+        assert_eq!(
+            serde_json::to_string(&SimulationFlags::ALL).unwrap(),
+            r#"["DIRECT","REFLECTIONS","PATHING"]"#
+        );
+        assert!(serde_json::from_str::<DirectEffectFlags>(r#"["LOUDNESS"]"#).is_err());
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let inputs = SimulationInputs {
+            direct_flags: DirectSimulationFlags::OCCLUSION | DirectSimulationFlags::TRANSMISSION,
+            source: Orientation {
+                origin: [1.0, 2.0, 3.0],
+                ..Default::default()
+            },
+            distance_attenuation_model: DistanceAttenuationModel::InverseDistance {
+                min_distance: 2.0,
+            },
+            ..Default::default()
+        };
+        let toml = toml::to_string(&inputs).unwrap();
+        let decoded: SimulationInputs = toml::from_str(&toml).unwrap();
+        assert_eq!(decoded.direct_flags, inputs.direct_flags);
+        assert_eq!(decoded.source.origin, inputs.source.origin);
+        assert!(matches!(
+            decoded.distance_attenuation_model,
+            DistanceAttenuationModel::InverseDistance { min_distance } if min_distance == 2.0
+        ));
+
+        let audio_settings = AudioSettings::new(48000, 256).unwrap();
+        let json = serde_json::to_string(&audio_settings).unwrap();
+        assert_eq!(
+            serde_json::from_str::<AudioSettings>(&json).unwrap(),
+            audio_settings
+        );
+        assert!(serde_json::from_str::<AudioSettings>(
+            r#"{"sampling_rate": 48000, "frame_size": 100}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! Loading [`StaticMeshSettings`] from `.obj` and `.gltf`/`.glb` files.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use gltf::mesh::Mode;
+
+use crate::materials;
+use crate::prelude::*;
+
+/// Geometry loaded from a file, ready for [`StaticMesh::new`].
+#[derive(Debug, Clone)]
+pub struct ImportedMesh {
+    pub settings: StaticMeshSettings,
+    /// Material names found in the file that weren't in the material table,
+    /// triangles using them got [`materials::GENERIC`].
+    pub unmapped_materials: Vec<String>,
+}
+
+/// Load `path` as obj or gltf depending on its extension.
+///
+/// `materials` maps the material names used in the file to acoustic materials.
+pub fn import<P: AsRef<Path>>(
+    path: P,
+    materials: &MaterialLibrary,
+) -> Result<ImportedMesh, SteamAudioError> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("obj") => import_obj(path, materials),
+        Some("gltf") | Some("glb") => import_gltf(path, materials),
+        _ => Err(SteamAudioError::Decode(
+            format!("unsupported mesh format {}", path.display()).into(),
+        )),
+    }
+}
+
+/// Load an obj file, faces are triangulated.
+pub fn import_obj<P: AsRef<Path>>(
+    path: P,
+    materials: &MaterialLibrary,
+) -> Result<ImportedMesh, SteamAudioError> {
+    let options = tobj::LoadOptions {
+        triangulate: true,
+        single_index: true,
+        ..Default::default()
+    };
+    let (models, obj_materials) = tobj::load_obj(path.as_ref(), &options).map_err(decode_error)?;
+    // A missing .mtl only costs us the names, everything falls back to generic.
+    let obj_materials = obj_materials.unwrap_or_default();
+
+    let mut builder = MeshBuilder::new(materials);
+    for model in &models {
+        let mesh = &model.mesh;
+        let name = mesh
+            .material_id
+            .and_then(|id| obj_materials.get(id))
+            .map(|material| material.name.as_str());
+        let material = builder.material(name);
+
+        let positions = mesh
+            .positions
+            .chunks_exact(3)
+            .map(|position| [position[0], position[1], position[2]]);
+        let base = builder.push_vertices(positions)?;
+        for indices in mesh.indices.chunks_exact(3) {
+            builder.push_triangle(base, [indices[0], indices[1], indices[2]], material)?;
+        }
+    }
+
+    Ok(builder.finish())
+}
+
+/// Load the default scene of a gltf or glb file, or every scene if there is no
+/// default, with node transforms applied.
+pub fn import_gltf<P: AsRef<Path>>(
+    path: P,
+    materials: &MaterialLibrary,
+) -> Result<ImportedMesh, SteamAudioError> {
+    let path = path.as_ref();
+    let gltf::Gltf { document, blob } = gltf::Gltf::open(path).map_err(decode_error)?;
+    let buffers = gltf::import_buffers(&document, path.parent(), blob).map_err(decode_error)?;
+
+    let mut builder = MeshBuilder::new(materials);
+    let scenes: Vec<_> = match document.default_scene() {
+        Some(scene) => vec![scene],
+        None => document.scenes().collect(),
+    };
+    for scene in scenes {
+        for node in scene.nodes() {
+            import_node(&mut builder, &buffers, node, IDENTITY)?;
+        }
+    }
+
+    Ok(builder.finish())
+}
+
+type Matrix = [[f32; 4]; 4];
+
+// Column major like gltf.
+const IDENTITY: Matrix = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut product = [[0.0; 4]; 4];
+    for (column, b_column) in product.iter_mut().zip(b) {
+        for (row, value) in column.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[k][row] * b_column[k]).sum();
+        }
+    }
+    product
+}
+
+fn transform_point(matrix: &Matrix, point: [f32; 3]) -> [f32; 3] {
+    let mut transformed = [0.0; 3];
+    for (row, value) in transformed.iter_mut().enumerate() {
+        *value = matrix[3][row] + (0..3).map(|k| matrix[k][row] * point[k]).sum::<f32>();
+    }
+    transformed
+}
+
+// Negative for transforms that mirror the geometry.
+fn determinant(matrix: &Matrix) -> f32 {
+    let [x, y, z] = [matrix[0], matrix[1], matrix[2]];
+    x[0] * (y[1] * z[2] - y[2] * z[1]) - y[0] * (x[1] * z[2] - x[2] * z[1])
+        + z[0] * (x[1] * y[2] - x[2] * y[1])
+}
+
+fn import_node(
+    builder: &mut MeshBuilder,
+    buffers: &[gltf::buffer::Data],
+    node: gltf::Node,
+    parent: Matrix,
+) -> Result<(), SteamAudioError> {
+    let transform = multiply(&parent, &node.transform().matrix());
+    let mirrored = determinant(&transform) < 0.0;
+
+    if let Some(mesh) = node.mesh() {
+        for primitive in mesh.primitives() {
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let positions = match reader.read_positions() {
+                Some(positions) => positions,
+                None => continue,
+            };
+
+            let vertex_count = positions.len() as u32;
+            let positions = positions.map(|position| transform_point(&transform, position));
+            let base = builder.push_vertices(positions)?;
+
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..vertex_count).collect(),
+            };
+
+            let material = builder.material(primitive.material().name());
+            for mut triangle in triangles(primitive.mode(), &indices) {
+                if mirrored {
+                    triangle.swap(1, 2);
+                }
+                builder.push_triangle(base, triangle, material)?;
+            }
+        }
+    }
+
+    for child in node.children() {
+        import_node(builder, buffers, child, transform)?;
+    }
+
+    Ok(())
+}
+
+// Points and lines have no surface, they are skipped.
+fn triangles(mode: Mode, indices: &[u32]) -> Vec<[u32; 3]> {
+    match mode {
+        Mode::Triangles => indices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect(),
+        Mode::TriangleStrip => (0..indices.len().saturating_sub(2))
+            .map(|index| {
+                // Every other triangle is wound the other way around.
+                if index % 2 == 0 {
+                    [indices[index], indices[index + 1], indices[index + 2]]
+                } else {
+                    [indices[index + 1], indices[index], indices[index + 2]]
+                }
+            })
+            .collect(),
+        Mode::TriangleFan => (1..indices.len().saturating_sub(1))
+            .map(|index| [indices[0], indices[index], indices[index + 1]])
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn decode_error<E>(error: E) -> SteamAudioError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    SteamAudioError::Decode(Box::new(error))
+}
+
+struct MeshBuilder<'a> {
+    table: &'a MaterialLibrary,
+    settings: StaticMeshSettings,
+    // Index into `settings.materials` for every name seen so far, `None` is
+    // the generic fallback.
+    indices: HashMap<Option<String>, i32>,
+    unmapped_materials: Vec<String>,
+}
+
+impl<'a> MeshBuilder<'a> {
+    fn new(table: &'a MaterialLibrary) -> Self {
+        Self {
+            table,
+            settings: StaticMeshSettings {
+                vertices: Vec::new(),
+                triangles: Vec::new(),
+                materials: Vec::new(),
+                material_indices: Vec::new(),
+            },
+            indices: HashMap::new(),
+            unmapped_materials: Vec::new(),
+        }
+    }
+
+    fn material(&mut self, name: Option<&str>) -> i32 {
+        let key = name.filter(|name| self.table.contains(name));
+        if let Some(name) = name {
+            if key.is_none()
+                && !self
+                    .unmapped_materials
+                    .iter()
+                    .any(|unmapped| unmapped == name)
+            {
+                self.unmapped_materials.push(name.to_owned());
+            }
+        }
+
+        let key = key.map(str::to_owned);
+        if let Some(&index) = self.indices.get(&key) {
+            return index;
+        }
+
+        let material = match &key {
+            Some(name) => self.table.get(name).cloned().unwrap_or(materials::GENERIC),
+            None => materials::GENERIC,
+        };
+        let index = self.settings.materials.len() as i32;
+        self.settings.materials.push(material);
+        self.indices.insert(key, index);
+        index
+    }
+
+    // Returns the index of the first vertex pushed.
+    fn push_vertices(
+        &mut self,
+        positions: impl Iterator<Item = [f32; 3]>,
+    ) -> Result<u32, SteamAudioError> {
+        let base = self.settings.vertices.len();
+        self.settings.vertices.extend(positions);
+        if i32::try_from(self.settings.vertices.len()).is_err() {
+            return Err(SteamAudioError::Decode("too many vertices".into()));
+        }
+
+        Ok(base as u32)
+    }
+
+    fn push_triangle(
+        &mut self,
+        base: u32,
+        triangle: [u32; 3],
+        material: i32,
+    ) -> Result<(), SteamAudioError> {
+        let mut indices = [0; 3];
+        for (index, vertex) in indices.iter_mut().zip(triangle) {
+            *index = base
+                .checked_add(vertex)
+                .and_then(|vertex| i32::try_from(vertex).ok())
+                .ok_or_else(|| SteamAudioError::Decode("vertex index out of range".into()))?;
+        }
+
+        self.settings.triangles.push(indices);
+        self.settings.material_indices.push(material);
+        Ok(())
+    }
+
+    fn finish(self) -> ImportedMesh {
+        ImportedMesh {
+            settings: self.settings,
+            unmapped_materials: self.unmapped_materials,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composes_transforms() {
+        let mut translate = IDENTITY;
+        translate[3] = [1.0, 2.0, 3.0, 1.0];
+        let mut scale = IDENTITY;
+        scale[0][0] = -2.0;
+
+        let transform = multiply(&translate, &scale);
+        assert_eq!(
+            transform_point(&transform, [1.0, 1.0, 1.0]),
+            [-1.0, 3.0, 4.0]
+        );
+        assert!(determinant(&transform) < 0.0);
+    }
+
+    #[test]
+    fn imports_obj_with_material_table() {
+        // Removed again when dropped.
+        let temp = tempfile::tempdir().unwrap();
+        let directory = temp.path();
+        std::fs::write(
+            directory.join("room.mtl"),
+            "newmtl brick\nKd 1 0 0\nnewmtl velvet\nKd 0 1 0\n",
+        )
+        .unwrap();
+        let obj = directory.join("room.obj");
+        std::fs::write(
+            &obj,
+            "mtllib room.mtl\n\
+             v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             o wall\nusemtl brick\nf 1 2 3 4\n\
+             o curtain\nusemtl velvet\nf 1 2 3\n",
+        )
+        .unwrap();
+
+        let mut table = MaterialLibrary::new();
+        table.insert("brick", materials::BRICK);
+        let imported = import(&obj, &table).unwrap();
+        let settings = &imported.settings;
+
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.triangles.len(), 3);
+        assert_eq!(imported.unmapped_materials, ["velvet"]);
+        assert_eq!(settings.materials.len(), 2);
+        assert_eq!(
+            settings.materials[settings.material_indices[0] as usize].absorption,
+            materials::BRICK.absorption
+        );
+        assert_eq!(
+            settings.materials[settings.material_indices[2] as usize].absorption,
+            materials::GENERIC.absorption
+        );
+    }
+}
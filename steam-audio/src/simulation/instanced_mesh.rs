@@ -0,0 +1,235 @@
+use steam_audio_sys::ffi;
+
+use crate::prelude::*;
+
+/// Affine transform placing an [`InstancedMesh`], row-major like steam audio.
+///
+/// The translation is in the last column, `rows[0][3]`, `rows[1][3]` and `rows[2][3]`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    pub rows: [[f32; 4]; 4],
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        rows: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    /// Transform from its columns, the way most math libraries store it.
+    pub fn from_columns(columns: [[f32; 4]; 4]) -> Self {
+        let mut rows = [[0.0; 4]; 4];
+        for (column_index, column) in columns.iter().enumerate() {
+            for (row, value) in rows.iter_mut().zip(column) {
+                row[column_index] = *value;
+            }
+        }
+        Self { rows }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl From<[[f32; 4]; 4]> for Transform {
+    fn from(rows: [[f32; 4]; 4]) -> Self {
+        Self { rows }
+    }
+}
+
+impl From<Transform> for ffi::IPLMatrix4x4 {
+    fn from(transform: Transform) -> Self {
+        Self {
+            elements: transform.rows,
+        }
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Mat4> for Transform {
+    fn from(transform: glam::Mat4) -> Self {
+        Self::from_columns(transform.to_cols_array_2d())
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Affine3A> for Transform {
+    fn from(transform: glam::Affine3A) -> Self {
+        Self::from(glam::Mat4::from(transform))
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<mint::ColumnMatrix4<f32>> for Transform {
+    fn from(transform: mint::ColumnMatrix4<f32>) -> Self {
+        Self::from_columns(transform.into())
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<mint::RowMatrix4<f32>> for Transform {
+    fn from(transform: mint::RowMatrix4<f32>) -> Self {
+        Self {
+            rows: transform.into(),
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Matrix4<f32>> for Transform {
+    fn from(transform: nalgebra::Matrix4<f32>) -> Self {
+        Self::from_columns(transform.into())
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Isometry3<f32>> for Transform {
+    fn from(transform: nalgebra::Isometry3<f32>) -> Self {
+        Self::from(transform.to_homogeneous())
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Affine3<f32>> for Transform {
+    fn from(transform: nalgebra::Affine3<f32>) -> Self {
+        Self::from(transform.to_homogeneous())
+    }
+}
+
+/// A copy of another scene placed in a scene with a [`Transform`], e.g. a
+/// piece of furniture used all over a level.
+///
+/// Moving the instance only updates its transform, the geometry of the sub
+/// scene isn't rebuilt.
+pub struct InstancedMesh {
+    inner: ffi::IPLInstancedMesh,
+    // Retained, the instance keeps pointing into it.
+    _sub_scene: Scene,
+}
+
+unsafe impl Send for InstancedMesh {}
+unsafe impl Sync for InstancedMesh {}
+
+impl crate::SteamAudioObject for InstancedMesh {
+    type Object = ffi::IPLInstancedMesh;
+    fn inner_raw(&self) -> Self::Object {
+        assert!(!self.inner.is_null());
+        self.inner
+    }
+    fn inner_mut(&mut self) -> *mut Self::Object {
+        std::ptr::addr_of_mut!(self.inner)
+    }
+}
+
+impl InstancedMesh {
+    /// Instance `sub_scene` in `scene`, call [`Scene::add_instanced_mesh`] to make it audible.
+    ///
+    /// `sub_scene` has to be committed before `scene` is.
+    pub fn new(
+        scene: &Scene,
+        sub_scene: &Scene,
+        transform: impl Into<Transform>,
+    ) -> Result<Self, SteamAudioError> {
+        let mut mesh = Self {
+            inner: std::ptr::null_mut(),
+            _sub_scene: sub_scene.retain(),
+        };
+
+        let mut settings = ffi::IPLInstancedMeshSettings {
+            subScene: sub_scene.inner_raw(),
+            transform: transform.into().into(),
+        };
+
+        unsafe {
+            match ffi::iplInstancedMeshCreate(scene.inner_raw(), &mut settings, mesh.inner_mut()) {
+                ffi::IPLerror::IPL_STATUS_SUCCESS => Ok(mesh),
+                err => Err(SteamAudioError::IPLError(err)),
+            }
+        }
+    }
+
+    /// Move the instance, takes effect on the next [`Scene::commit`].
+    pub fn update_transform(&self, scene: &Scene, transform: impl Into<Transform>) {
+        unsafe {
+            ffi::iplInstancedMeshUpdateTransform(
+                self.inner_raw(),
+                scene.inner_raw(),
+                transform.into().into(),
+            );
+        }
+    }
+}
+
+impl Drop for InstancedMesh {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::iplInstancedMeshRelease(self.inner_mut());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_become_rows() {
+        let translation = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [2.0, 3.0, 4.0, 1.0],
+        ];
+        let transform = Transform::from_columns(translation);
+        assert_eq!(transform.rows[0], [1.0, 0.0, 0.0, 2.0]);
+        assert_eq!(transform.rows[1], [0.0, 1.0, 0.0, 3.0]);
+        assert_eq!(transform.rows[2], [0.0, 0.0, 1.0, 4.0]);
+        assert_eq!(transform.rows[3], [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(Transform::default(), Transform::IDENTITY);
+    }
+
+    #[cfg(all(feature = "glam", feature = "mint", feature = "nalgebra"))]
+    #[test]
+    fn math_libraries_agree() {
+        let rotation = glam::Quat::from_euler(glam::EulerRot::YXZ, 0.3, -0.7, 1.1);
+        let origin = glam::Vec3::new(1.0, -2.0, 3.0);
+        let matrix = glam::Mat4::from_rotation_translation(rotation, origin);
+        let expected = Transform::from(matrix);
+        assert_eq!(expected.rows[0][3], 1.0);
+        assert_eq!(expected.rows[1][3], -2.0);
+        assert_eq!(expected.rows[2][3], 3.0);
+
+        let from_affine =
+            Transform::from(glam::Affine3A::from_rotation_translation(rotation, origin));
+        let from_mint = Transform::from(mint::ColumnMatrix4::from(matrix.to_cols_array_2d()));
+        let from_nalgebra = Transform::from(nalgebra::Matrix4::from_column_slice(
+            &matrix.to_cols_array(),
+        ));
+        let isometry = nalgebra::Isometry3::from_parts(
+            nalgebra::Translation3::new(origin.x, origin.y, origin.z),
+            nalgebra::UnitQuaternion::from_quaternion(nalgebra::Quaternion::new(
+                rotation.w, rotation.x, rotation.y, rotation.z,
+            )),
+        );
+
+        for transform in [
+            from_affine,
+            from_mint,
+            from_nalgebra,
+            Transform::from(isometry),
+        ] {
+            for (row, expected) in transform.rows.iter().zip(&expected.rows) {
+                for (value, expected) in row.iter().zip(expected) {
+                    assert!((value - expected).abs() < 1e-5, "{:?}", transform);
+                }
+            }
+        }
+    }
+}
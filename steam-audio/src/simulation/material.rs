@@ -1,7 +1,12 @@
+use std::collections::BTreeMap;
+
 use steam_audio_sys::ffi;
 
+use crate::error::SteamAudioError;
+
 /// Acoustic properties of a surface.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Material {
     // Specified in 3 frequency bands of 400 Hz, 2.5KHz, and 15 KHz.
     pub absorption: [f32; 3],
@@ -9,12 +14,96 @@ pub struct Material {
     pub transmission: [f32; 3],
 }
 
-impl Into<ffi::IPLMaterial> for &Material {
-    fn into(self) -> ffi::IPLMaterial {
+impl Material {
+    /// Center frequencies in Hz of the bands `absorption` and `transmission` are specified in.
+    pub const BAND_FREQUENCIES: [f32; 3] = [400.0, 2500.0, 15000.0];
+
+    /// Build a material from published octave band data, e.g. 125 Hz to 4 kHz tables.
+    ///
+    /// `absorption` and `transmission_loss` are `(frequency in Hz, value)` pairs
+    /// in any order. They are interpolated linearly over log frequency onto
+    /// [`Self::BAND_FREQUENCIES`], bands outside the data are extrapolated from
+    /// the closest two points. Transmission loss is in dB and converted to the
+    /// fraction of energy transmitted, `10^(-loss / 10)`.
+    pub fn from_octave_bands(
+        absorption: &[(f32, f32)],
+        scattering: f32,
+        transmission_loss: &[(f32, f32)],
+    ) -> Result<Self, SteamAudioError> {
+        let absorption = to_bands(absorption, "absorption")?.map(|value| value.clamp(0.0, 1.0));
+        let transmission = to_bands(transmission_loss, "transmission loss")?
+            .map(|loss| 10f32.powf(-loss.max(0.0) / 10.0));
+
+        let material = Self {
+            absorption,
+            scattering,
+            transmission,
+        };
+        material.check_ranges()?;
+        Ok(material)
+    }
+
+    /// Every coefficient has to be between 0 and 1.
+    pub fn check_ranges(&self) -> Result<(), SteamAudioError> {
+        let values = [
+            ("absorption", self.absorption.as_slice()),
+            ("scattering", std::slice::from_ref(&self.scattering)),
+            ("transmission", self.transmission.as_slice()),
+        ];
+        for (name, values) in values {
+            if let Some(value) = values.iter().find(|value| !(0.0..=1.0).contains(*value)) {
+                return Err(SteamAudioError::InvalidMaterial(format!(
+                    "{} {} is outside of 0 to 1",
+                    name, value
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Resample `(frequency, value)` pairs onto the steam audio bands.
+fn to_bands(points: &[(f32, f32)], name: &str) -> Result<[f32; 3], SteamAudioError> {
+    let invalid = |reason: &str| SteamAudioError::InvalidMaterial(format!("{} {}", name, reason));
+
+    let mut points = points
+        .iter()
+        .map(|&(frequency, value)| {
+            if frequency <= 0.0 || !frequency.is_finite() || !value.is_finite() {
+                return Err(invalid("has a non-positive or non-finite entry"));
+            }
+            Ok((frequency.log2(), value))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    if points.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+        return Err(invalid("has the same frequency twice"));
+    }
+
+    match points.as_slice() {
+        [] => Err(invalid("is empty")),
+        [(_, value)] => Ok([*value; 3]),
+        _ => Ok(Material::BAND_FREQUENCIES.map(|frequency| {
+            let x = frequency.log2();
+            // The segment containing `x`, or the closest one at either end.
+            let segment = points
+                .windows(2)
+                .position(|pair| x <= pair[1].0)
+                .unwrap_or(points.len() - 2);
+            let ((x0, y0), (x1, y1)) = (points[segment], points[segment + 1]);
+            y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+        })),
+    }
+}
+
+impl From<&Material> for ffi::IPLMaterial {
+    fn from(material: &Material) -> Self {
         ffi::IPLMaterial {
-            absorption: self.absorption,
-            scattering: self.scattering,
-            transmission: self.transmission,
+            absorption: material.absorption,
+            scattering: material.scattering,
+            transmission: material.transmission,
         }
     }
 }
@@ -24,57 +113,330 @@ pub mod materials {
 
     pub const GENERIC: Material = Material {
         absorption: [0.10, 0.20, 0.30],
-        scattering: 0.05,
+        scattering: 0.10,
         transmission: [0.100, 0.050, 0.030],
     };
     pub const BRICK: Material = Material {
         absorption: [0.03, 0.04, 0.07],
-        scattering: 0.05,
+        scattering: 0.15,
         transmission: [0.015, 0.015, 0.015],
     };
     pub const CONCRETE: Material = Material {
         absorption: [0.05, 0.07, 0.08],
-        scattering: 0.05,
+        scattering: 0.10,
         transmission: [0.015, 0.002, 0.001],
     };
     pub const CERAMIC: Material = Material {
         absorption: [0.01, 0.02, 0.02],
-        scattering: 0.05,
+        scattering: 0.03,
         transmission: [0.060, 0.044, 0.011],
     };
     pub const GRAVEL: Material = Material {
         absorption: [0.60, 0.70, 0.80],
-        scattering: 0.05,
+        scattering: 0.60,
         transmission: [0.031, 0.012, 0.008],
     };
     pub const CARPET: Material = Material {
         absorption: [0.24, 0.69, 0.73],
-        scattering: 0.05,
+        scattering: 0.20,
         transmission: [0.020, 0.005, 0.003],
     };
     pub const GLASS: Material = Material {
         absorption: [0.06, 0.03, 0.02],
-        scattering: 0.05,
+        scattering: 0.02,
         transmission: [0.060, 0.044, 0.011],
     };
     pub const PLASTER: Material = Material {
         absorption: [0.12, 0.06, 0.04],
-        scattering: 0.05,
+        scattering: 0.08,
         transmission: [0.056, 0.056, 0.004],
     };
     pub const WOOD: Material = Material {
         absorption: [0.11, 0.07, 0.06],
-        scattering: 0.05,
+        scattering: 0.10,
         transmission: [0.070, 0.014, 0.005],
     };
     pub const METAL: Material = Material {
         absorption: [0.20, 0.07, 0.06],
-        scattering: 0.05,
+        scattering: 0.04,
         transmission: [0.200, 0.025, 0.010],
     };
     pub const ROCK: Material = Material {
         absorption: [0.13, 0.20, 0.24],
-        scattering: 0.05,
+        scattering: 0.40,
         transmission: [0.015, 0.002, 0.001],
     };
+    pub const WATER: Material = Material {
+        absorption: [0.01, 0.01, 0.02],
+        scattering: 0.02,
+        transmission: [0.030, 0.010, 0.005],
+    };
+    pub const FOLIAGE: Material = Material {
+        absorption: [0.25, 0.40, 0.50],
+        scattering: 0.70,
+        transmission: [0.500, 0.300, 0.200],
+    };
+    pub const SNOW: Material = Material {
+        absorption: [0.45, 0.75, 0.90],
+        scattering: 0.30,
+        transmission: [0.020, 0.010, 0.005],
+    };
+    pub const ACOUSTIC_TILE: Material = Material {
+        absorption: [0.50, 0.75, 0.70],
+        scattering: 0.15,
+        transmission: [0.060, 0.030, 0.015],
+    };
+    pub const CURTAIN: Material = Material {
+        absorption: [0.35, 0.60, 0.65],
+        scattering: 0.20,
+        transmission: [0.300, 0.200, 0.100],
+    };
+    pub const UPHOLSTERED_SEATS: Material = Material {
+        absorption: [0.60, 0.80, 0.80],
+        scattering: 0.60,
+        transmission: [0.050, 0.030, 0.020],
+    };
+
+    /// Every built-in material by its name in a [`MaterialLibrary`](super::MaterialLibrary).
+    pub const ALL: [(&str, Material); 17] = [
+        ("generic", GENERIC),
+        ("brick", BRICK),
+        ("concrete", CONCRETE),
+        ("ceramic", CERAMIC),
+        ("gravel", GRAVEL),
+        ("carpet", CARPET),
+        ("glass", GLASS),
+        ("plaster", PLASTER),
+        ("wood", WOOD),
+        ("metal", METAL),
+        ("rock", ROCK),
+        ("water", WATER),
+        ("foliage", FOLIAGE),
+        ("snow", SNOW),
+        ("acoustic_tile", ACOUSTIC_TILE),
+        ("curtain", CURTAIN),
+        ("upholstered_seats", UPHOLSTERED_SEATS),
+    ];
+}
+
+/// Named materials, e.g. loaded from a file sound designers can tune without
+/// recompiling.
+///
+/// With the `serde` feature this is a table of materials keyed by name:
+///
+/// ```toml
+/// [brick]
+/// absorption = [0.03, 0.04, 0.07]
+/// scattering = 0.15
+/// transmission = [0.015, 0.015, 0.015]
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct MaterialLibrary {
+    materials: BTreeMap<String, Material>,
+}
+
+impl MaterialLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Library with everything in [`materials::ALL`].
+    pub fn builtin() -> Self {
+        materials::ALL
+            .iter()
+            .map(|(name, material)| (name.to_string(), material.clone()))
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Material> {
+        self.materials.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.materials.contains_key(name)
+    }
+
+    /// Add or replace a material, returning the one it replaced.
+    pub fn insert(&mut self, name: impl Into<String>, material: Material) -> Option<Material> {
+        self.materials.insert(name.into(), material)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Material> {
+        self.materials.remove(name)
+    }
+
+    /// Add every material from `other`, replacing materials with the same name.
+    pub fn merge(&mut self, other: MaterialLibrary) {
+        self.materials.extend(other.materials);
+    }
+
+    /// Materials sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Material)> {
+        self.materials
+            .iter()
+            .map(|(name, material)| (name.as_str(), material))
+    }
+
+    pub fn len(&self) -> usize {
+        self.materials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.materials.is_empty()
+    }
+
+    /// Check every material with [`Material::check_ranges`].
+    pub fn check_ranges(&self) -> Result<(), SteamAudioError> {
+        for (name, material) in self.iter() {
+            material.check_ranges().map_err(|error| match error {
+                SteamAudioError::InvalidMaterial(reason) => {
+                    SteamAudioError::InvalidMaterial(format!("{}: {}", name, reason))
+                }
+                error => error,
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromIterator<(String, Material)> for MaterialLibrary {
+    fn from_iter<I: IntoIterator<Item = (String, Material)>>(iter: I) -> Self {
+        Self {
+            materials: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl MaterialLibrary {
+    /// Fails on materials outside of [`Material::check_ranges`] too.
+    pub fn from_toml_str(toml: &str) -> Result<Self, SteamAudioError> {
+        let library: Self =
+            toml::from_str(toml).map_err(|error| SteamAudioError::Decode(Box::new(error)))?;
+        library.check_ranges()?;
+        Ok(library)
+    }
+
+    /// Fails on materials outside of [`Material::check_ranges`] too.
+    pub fn from_json_str(json: &str) -> Result<Self, SteamAudioError> {
+        let library: Self =
+            serde_json::from_str(json).map_err(|error| SteamAudioError::Decode(Box::new(error)))?;
+        library.check_ranges()?;
+        Ok(library)
+    }
+
+    /// Load a `.toml` or `.json` library depending on the extension of `path`.
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, SteamAudioError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml_str(&contents),
+            Some("json") => Self::from_json_str(&contents),
+            _ => Err(SteamAudioError::Decode(
+                format!("unsupported material library format {}", path.display()).into(),
+            )),
+        }
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, SteamAudioError> {
+        toml::to_string(self).map_err(|error| SteamAudioError::Decode(Box::new(error)))
+    }
+
+    pub fn to_json_string(&self) -> Result<String, SteamAudioError> {
+        serde_json::to_string_pretty(self).map_err(|error| SteamAudioError::Decode(Box::new(error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_lookup_by_name() {
+        let library = MaterialLibrary::builtin();
+        assert_eq!(library.len(), materials::ALL.len());
+        assert_eq!(library.get("snow"), Some(&materials::SNOW));
+        assert_eq!(
+            library.get("acoustic_tile"),
+            Some(&materials::ACOUSTIC_TILE)
+        );
+        assert_eq!(library.get("vibranium"), None);
+        assert!(library.check_ranges().is_ok());
+    }
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        for (a, b) in a.iter().zip(&b) {
+            assert!((a - b).abs() < 1e-4, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn converts_octave_bands() {
+        // Linear in the octave number so interpolation is exact.
+        let octaves = [125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0];
+        let absorption: Vec<_> = octaves
+            .iter()
+            .enumerate()
+            .map(|(octave, frequency)| (*frequency, 0.15 * octave as f32))
+            .rev()
+            .collect();
+        let loss = [(500.0, 30.0), (1000.0, 40.0)];
+
+        let material = Material::from_octave_bands(&absorption, 0.3, &loss).unwrap();
+        let expected =
+            Material::BAND_FREQUENCIES.map(|frequency| (frequency / 125.0).log2() * 0.15);
+        assert_close(material.absorption, expected.map(|value| value.min(1.0)));
+        assert_eq!(material.absorption[2], 1.0);
+
+        let expected = Material::BAND_FREQUENCIES
+            .map(|frequency| 30.0 + 10.0 * (frequency / 500.0).log2())
+            .map(|loss| 10f32.powf(-loss / 10.0));
+        assert_close(material.transmission, expected);
+        assert_eq!(material.scattering, 0.3);
+    }
+
+    #[test]
+    fn rejects_bad_band_data() {
+        let absorption = [(500.0, 0.2)];
+        assert!(Material::from_octave_bands(&absorption, 0.1, &[]).is_err());
+        assert!(Material::from_octave_bands(&absorption, 1.5, &absorption).is_err());
+        assert!(Material::from_octave_bands(&[(0.0, 0.1)], 0.1, &absorption).is_err());
+        assert!(
+            Material::from_octave_bands(&[(500.0, 0.1), (500.0, 0.2)], 0.1, &absorption).is_err()
+        );
+
+        let flat = Material::from_octave_bands(&absorption, 0.1, &[(1000.0, 0.0)]).unwrap();
+        assert_eq!(flat.absorption, [0.2; 3]);
+        assert_eq!(flat.transmission, [1.0; 3]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_through_toml_and_json() {
+        let library = MaterialLibrary::builtin();
+        let toml = library.to_toml_string().unwrap();
+        assert_eq!(MaterialLibrary::from_toml_str(&toml).unwrap(), library);
+        let json = library.to_json_string().unwrap();
+        assert_eq!(MaterialLibrary::from_json_str(&json).unwrap(), library);
+
+        let tuned = MaterialLibrary::from_toml_str(
+            "[stage]\nabsorption = [0.1, 0.2, 0.3]\nscattering = 0.4\ntransmission = [0.0, 0.0, 0.0]\n",
+        )
+        .unwrap();
+        assert_eq!(tuned.get("stage").unwrap().scattering, 0.4);
+        assert!(MaterialLibrary::from_json_str("{\"stage\": {}}").is_err());
+
+        let loud = MaterialLibrary::from_json_str(
+            "{\"stage\": {\"absorption\": [0.1, 1.2, 0.3], \"scattering\": 0.4, \"transmission\": [0.0, 0.0, 0.0]}}",
+        );
+        assert!(
+            matches!(loud, Err(SteamAudioError::InvalidMaterial(reason)) if reason == "stage: absorption 1.2 is outside of 0 to 1")
+        );
+    }
 }
@@ -1,5 +1,10 @@
+#[cfg(feature = "import")]
+pub mod import;
+pub mod instanced_mesh;
 pub mod material;
 pub mod scene;
+pub mod shapes;
+#[allow(clippy::module_inception)]
 pub mod simulation;
 pub mod source;
 pub mod static_mesh;
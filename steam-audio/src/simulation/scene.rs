@@ -2,8 +2,9 @@ use steam_audio_sys::ffi;
 
 use crate::prelude::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum SceneSettings {
+    #[default]
     Default,
     /*
     TODO: implement this stuff
@@ -15,14 +16,8 @@ pub enum SceneSettings {
     */
 }
 
-impl Default for SceneSettings {
-    fn default() -> Self {
-        Self::Default
-    }
-}
-
-impl Into<ffi::IPLSceneSettings> for &SceneSettings {
-    fn into(self) -> ffi::IPLSceneSettings {
+impl From<&SceneSettings> for ffi::IPLSceneSettings {
+    fn from(scene_settings: &SceneSettings) -> Self {
         let mut model = ffi::IPLSceneSettings {
             type_: ffi::IPLSceneType::IPL_SCENETYPE_DEFAULT,
             closestHitCallback: None,
@@ -34,13 +29,13 @@ impl Into<ffi::IPLSceneSettings> for &SceneSettings {
             radeonRaysDevice: std::ptr::null_mut(),
         };
 
-        match self {
+        match scene_settings {
             SceneSettings::Default => {
                 model.type_ = ffi::IPLSceneType::IPL_SCENETYPE_DEFAULT;
             } /*
-              Self::Embree => { }
-              Self::RadeonRays => { }
-              Self::Custom => { }
+              SceneSettings::Embree => { }
+              SceneSettings::RadeonRays => { }
+              SceneSettings::Custom => { }
               */
         }
 
@@ -107,6 +102,27 @@ impl Scene {
             ffi::iplStaticMeshAdd(static_mesh.inner_raw(), self.inner_raw());
         }
     }
+
+    pub fn add_instanced_mesh(&self, instanced_mesh: &InstancedMesh) {
+        unsafe {
+            ffi::iplInstancedMeshAdd(instanced_mesh.inner_raw(), self.inner_raw());
+        }
+    }
+
+    pub fn remove_instanced_mesh(&self, instanced_mesh: &InstancedMesh) {
+        unsafe {
+            ffi::iplInstancedMeshRemove(instanced_mesh.inner_raw(), self.inner_raw());
+        }
+    }
+
+    pub fn retain(&self) -> Scene {
+        unsafe {
+            Scene {
+                inner: ffi::iplSceneRetain(self.inner_raw()),
+                settings: self.settings,
+            }
+        }
+    }
 }
 
 impl Drop for Scene {
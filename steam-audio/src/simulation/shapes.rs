@@ -0,0 +1,267 @@
+//! Procedural geometry for prototyping scenes and tests without external assets.
+//!
+//! Shapes are centered on the origin with y up, like the rest of steam audio.
+
+use std::f32::consts::TAU;
+
+use crate::prelude::*;
+
+/// Walls of a [`StaticMeshSettings::shoebox`], usable as an index into its materials.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Wall {
+    /// -x
+    Left,
+    /// +x
+    Right,
+    /// -y
+    Floor,
+    /// +y
+    Ceiling,
+    /// -z, the direction a default [`Orientation`] faces.
+    Front,
+    /// +z
+    Back,
+}
+
+impl Wall {
+    pub const ALL: [Wall; 6] = [
+        Wall::Left,
+        Wall::Right,
+        Wall::Floor,
+        Wall::Ceiling,
+        Wall::Front,
+        Wall::Back,
+    ];
+
+    fn axis(&self) -> usize {
+        *self as usize / 2
+    }
+
+    fn positive(&self) -> bool {
+        *self as usize % 2 == 1
+    }
+}
+
+impl StaticMeshSettings {
+    fn empty() -> Self {
+        Self {
+            vertices: Vec::new(),
+            triangles: Vec::new(),
+            materials: Vec::new(),
+            material_indices: Vec::new(),
+        }
+    }
+
+    /// The inside of a rectangular room, `dimensions` is width, height and depth.
+    ///
+    /// The floor sits at y = 0 and every wall faces into the room.
+    pub fn shoebox(dimensions: [f32; 3], materials: [Material; 6]) -> Self {
+        let [width, height, depth] = dimensions;
+        let min = [-width / 2.0, 0.0, -depth / 2.0];
+        let max = [width / 2.0, height, depth / 2.0];
+
+        let mut mesh = Self::empty();
+        mesh.materials = materials.to_vec();
+        for wall in Wall::ALL {
+            mesh.push_face(min, max, wall, true, wall as i32);
+        }
+        mesh
+    }
+
+    /// A `size[0]` by `size[1]` rectangle on the xz plane, facing up.
+    pub fn plane(size: [f32; 2], material: Material) -> Self {
+        let [width, depth] = size;
+        let min = [-width / 2.0, 0.0, -depth / 2.0];
+        let max = [width / 2.0, 0.0, depth / 2.0];
+
+        let mut mesh = Self::empty();
+        mesh.materials.push(material);
+        mesh.push_face(min, max, Wall::Ceiling, false, 0);
+        mesh
+    }
+
+    /// A solid box, facing outwards.
+    pub fn cuboid(size: [f32; 3], material: Material) -> Self {
+        let max = size.map(|axis| axis / 2.0);
+        let min = max.map(|axis| -axis);
+
+        let mut mesh = Self::empty();
+        mesh.materials.push(material);
+        for wall in Wall::ALL {
+            mesh.push_face(min, max, wall, false, 0);
+        }
+        mesh
+    }
+
+    /// A closed cylinder along the y axis, facing outwards.
+    ///
+    /// `segments` is how many sides approximate the circle, at least 3.
+    pub fn cylinder(radius: f32, height: f32, segments: usize, material: Material) -> Self {
+        let segments = segments.max(3);
+        let (bottom, top) = (-height / 2.0, height / 2.0);
+
+        let mut mesh = Self::empty();
+        mesh.materials.push(material);
+
+        // Bottom and top ring, then the centers of both caps.
+        for y in [bottom, top] {
+            mesh.vertices.extend((0..segments).map(|segment| {
+                let angle = TAU * segment as f32 / segments as f32;
+                [radius * angle.cos(), y, radius * angle.sin()]
+            }));
+        }
+        mesh.vertices.push([0.0, bottom, 0.0]);
+        mesh.vertices.push([0.0, top, 0.0]);
+
+        let ring = segments as i32;
+        let (bottom_center, top_center) = (2 * ring, 2 * ring + 1);
+        for segment in 0..ring {
+            let next = (segment + 1) % ring;
+            let (b0, b1, t0, t1) = (segment, next, ring + segment, ring + next);
+
+            mesh.triangles.push([b0, t0, t1]);
+            mesh.triangles.push([b0, t1, b1]);
+            mesh.triangles.push([bottom_center, b0, b1]);
+            mesh.triangles.push([top_center, t1, t0]);
+        }
+        mesh.material_indices = vec![0; mesh.triangles.len()];
+        mesh
+    }
+
+    /// Move every vertex by `offset`.
+    pub fn translated(mut self, offset: [f32; 3]) -> Self {
+        for vertex in &mut self.vertices {
+            for (axis, offset) in vertex.iter_mut().zip(offset) {
+                *axis += offset;
+            }
+        }
+        self
+    }
+
+    /// Append `other` to this mesh, so several shapes can go into one [`StaticMesh`].
+    pub fn merge(&mut self, other: &StaticMeshSettings) {
+        let vertex_offset = self.vertices.len() as i32;
+        let material_offset = self.materials.len() as i32;
+
+        self.vertices.extend_from_slice(&other.vertices);
+        self.materials.extend_from_slice(&other.materials);
+        self.triangles.extend(
+            other
+                .triangles
+                .iter()
+                .map(|triangle| triangle.map(|index| index + vertex_offset)),
+        );
+        self.material_indices.extend(
+            other
+                .material_indices
+                .iter()
+                .map(|index| index + material_offset),
+        );
+    }
+
+    /// Merge all of `meshes` into a single mesh.
+    pub fn merged<'a>(meshes: impl IntoIterator<Item = &'a StaticMeshSettings>) -> Self {
+        let mut merged = Self::empty();
+        for mesh in meshes {
+            merged.merge(mesh);
+        }
+        merged
+    }
+
+    // One side of the box spanning `min` to `max`, as two triangles.
+    fn push_face(&mut self, min: [f32; 3], max: [f32; 3], wall: Wall, inward: bool, material: i32) {
+        let axis = wall.axis();
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+
+        // Going around u then v winds counter-clockwise around +axis.
+        let mut corners = [(false, false), (true, false), (true, true), (false, true)];
+        if wall.positive() == inward {
+            corners.reverse();
+        }
+
+        let base = self.vertices.len() as i32;
+        for (u_max, v_max) in corners {
+            let mut vertex = if wall.positive() { max } else { min };
+            vertex[u] = if u_max { max[u] } else { min[u] };
+            vertex[v] = if v_max { max[v] } else { min[v] };
+            self.vertices.push(vertex);
+        }
+
+        self.triangles.push([base, base + 1, base + 2]);
+        self.triangles.push([base, base + 2, base + 3]);
+        self.material_indices.extend([material; 2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials;
+
+    // Signed volume, positive when every triangle faces outwards.
+    fn volume(mesh: &StaticMeshSettings) -> f32 {
+        mesh.triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|index| mesh.vertices[index as usize]);
+                let cross = [
+                    b[1] * c[2] - b[2] * c[1],
+                    b[2] * c[0] - b[0] * c[2],
+                    b[0] * c[1] - b[1] * c[0],
+                ];
+                (a[0] * cross[0] + a[1] * cross[1] + a[2] * cross[2]) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn shoebox_faces_inwards() {
+        let mut walls = [materials::BRICK; 6];
+        walls[Wall::Floor as usize] = materials::CARPET;
+        let room = StaticMeshSettings::shoebox([4.0, 3.0, 5.0], walls);
+
+        assert!(room.validate().is_ok());
+        assert_eq!(room.triangles.len(), 12);
+        assert!((volume(&room) + 60.0).abs() < 1e-4);
+
+        // The floor is the only triangles at y = 0 and uses the floor material.
+        for (triangle, material) in room.triangles.iter().zip(&room.material_indices) {
+            let on_floor = triangle
+                .iter()
+                .all(|index| room.vertices[*index as usize][1] == 0.0);
+            assert_eq!(on_floor, *material == Wall::Floor as i32);
+        }
+    }
+
+    #[test]
+    fn solids_face_outwards() {
+        let cuboid = StaticMeshSettings::cuboid([1.0, 2.0, 3.0], materials::WOOD);
+        assert!(cuboid.validate().is_ok());
+        assert!((volume(&cuboid) - 6.0).abs() < 1e-4);
+
+        let segments = 32;
+        let cylinder = StaticMeshSettings::cylinder(1.0, 2.0, segments, materials::METAL);
+        assert!(cylinder.validate().is_ok());
+        let polygon_area = segments as f32 / 2.0 * (TAU / segments as f32).sin();
+        assert!((volume(&cylinder) - polygon_area * 2.0).abs() < 1e-3);
+
+        let plane = StaticMeshSettings::plane([2.0, 2.0], materials::CONCRETE);
+        assert!(plane.validate().is_ok());
+    }
+
+    #[test]
+    fn merge_offsets_indices() {
+        let plane = StaticMeshSettings::plane([1.0, 1.0], materials::GRAVEL);
+        let cuboid = StaticMeshSettings::cuboid([1.0, 1.0, 1.0], materials::GLASS)
+            .translated([0.0, 2.0, 0.0]);
+        let merged = StaticMeshSettings::merged([&plane, &cuboid]);
+
+        assert!(merged.validate().is_ok());
+        assert_eq!(merged.vertices.len(), 4 + 24);
+        assert_eq!(merged.triangles.len(), 2 + 12);
+        assert_eq!(merged.materials.len(), 2);
+        assert_eq!(merged.triangles[2], [4, 5, 6]);
+        assert_eq!(merged.material_indices[2], 1);
+        assert!((volume(&merged) - 1.0).abs() < 1e-4);
+    }
+}
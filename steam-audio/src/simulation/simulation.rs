@@ -14,15 +14,22 @@ bitflags! {
     }
 }
 
+#[cfg(feature = "serde")]
+crate::serde_flags::serde_flags!(SimulationFlags {
+    DIRECT,
+    REFLECTIONS,
+    PATHING
+});
+
 impl Default for SimulationFlags {
     fn default() -> Self {
         Self::DEFAULT
     }
 }
 
-impl Into<ffi::IPLSimulationFlags> for SimulationFlags {
-    fn into(self) -> ffi::IPLSimulationFlags {
-        ffi::IPLSimulationFlags(self.bits())
+impl From<SimulationFlags> for ffi::IPLSimulationFlags {
+    fn from(simulation_flags: SimulationFlags) -> Self {
+        ffi::IPLSimulationFlags(simulation_flags.bits())
     }
 }
 
@@ -32,23 +39,19 @@ impl From<ffi::IPLSimulationFlags> for SimulationFlags {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SceneType {
+    #[default]
     Default,
     Embree,
     RadeonRays,
     Custom,
 }
 
-impl Default for SceneType {
-    fn default() -> Self {
-        Self::Default
-    }
-}
-
-impl Into<ffi::IPLSceneType> for SceneType {
-    fn into(self) -> ffi::IPLSceneType {
-        match self {
+impl From<SceneType> for ffi::IPLSceneType {
+    fn from(scene_type: SceneType) -> Self {
+        match scene_type {
             SceneType::Default => ffi::IPLSceneType::IPL_SCENETYPE_DEFAULT,
             SceneType::Embree => ffi::IPLSceneType::IPL_SCENETYPE_EMBREE,
             SceneType::RadeonRays => ffi::IPLSceneType::IPL_SCENETYPE_RADEONRAYS,
@@ -57,23 +60,19 @@ impl Into<ffi::IPLSceneType> for SceneType {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReflectionEffectType {
+    #[default]
     Convolution,
     Parametric,
     Hybrid,
     Tan,
 }
 
-impl Default for ReflectionEffectType {
-    fn default() -> Self {
-        Self::Convolution
-    }
-}
-
-impl Into<ffi::IPLReflectionEffectType> for ReflectionEffectType {
-    fn into(self) -> ffi::IPLReflectionEffectType {
-        match self {
+impl From<ReflectionEffectType> for ffi::IPLReflectionEffectType {
+    fn from(reflection_effect_type: ReflectionEffectType) -> Self {
+        match reflection_effect_type {
             ReflectionEffectType::Convolution => {
                 ffi::IPLReflectionEffectType::IPL_REFLECTIONEFFECTTYPE_CONVOLUTION
             }
@@ -88,7 +87,25 @@ impl Into<ffi::IPLReflectionEffectType> for ReflectionEffectType {
     }
 }
 
+impl From<ffi::IPLReflectionEffectType> for ReflectionEffectType {
+    fn from(other: ffi::IPLReflectionEffectType) -> Self {
+        match other {
+            ffi::IPLReflectionEffectType::IPL_REFLECTIONEFFECTTYPE_CONVOLUTION => {
+                ReflectionEffectType::Convolution
+            }
+            ffi::IPLReflectionEffectType::IPL_REFLECTIONEFFECTTYPE_PARAMETRIC => {
+                ReflectionEffectType::Parametric
+            }
+            ffi::IPLReflectionEffectType::IPL_REFLECTIONEFFECTTYPE_HYBRID => {
+                ReflectionEffectType::Hybrid
+            }
+            ffi::IPLReflectionEffectType::IPL_REFLECTIONEFFECTTYPE_TAN => ReflectionEffectType::Tan,
+        }
+    }
+}
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimulationSettings {
     pub flags: SimulationFlags,
     pub scene_type: SceneType,
@@ -131,23 +148,23 @@ impl SimulationSettings {
     }
 }
 
-impl Into<ffi::IPLSimulationSettings> for &SimulationSettings {
-    fn into(self) -> ffi::IPLSimulationSettings {
+impl From<&SimulationSettings> for ffi::IPLSimulationSettings {
+    fn from(simulation_settings: &SimulationSettings) -> Self {
         ffi::IPLSimulationSettings {
-            flags: self.flags.into(),
-            sceneType: self.scene_type.into(),
-            reflectionType: self.reflection_type.into(),
-            maxNumOcclusionSamples: self.max_num_occlusion_samples as i32,
-            maxNumRays: self.max_num_rays as i32,
-            numDiffuseSamples: self.num_diffuse_samples as i32,
-            maxDuration: self.max_duration,
-            maxOrder: self.max_order as i32,
-            maxNumSources: self.max_num_sources as i32,
-            numThreads: self.num_threads as i32,
-            rayBatchSize: self.ray_batch_size as i32,
-            numVisSamples: self.num_vis_samples as i32,
-            samplingRate: self.sampling_rate as i32,
-            frameSize: self.frame_size as i32,
+            flags: simulation_settings.flags.into(),
+            sceneType: simulation_settings.scene_type.into(),
+            reflectionType: simulation_settings.reflection_type.into(),
+            maxNumOcclusionSamples: simulation_settings.max_num_occlusion_samples as i32,
+            maxNumRays: simulation_settings.max_num_rays as i32,
+            numDiffuseSamples: simulation_settings.num_diffuse_samples as i32,
+            maxDuration: simulation_settings.max_duration,
+            maxOrder: simulation_settings.max_order as i32,
+            maxNumSources: simulation_settings.max_num_sources as i32,
+            numThreads: simulation_settings.num_threads as i32,
+            rayBatchSize: simulation_settings.ray_batch_size as i32,
+            numVisSamples: simulation_settings.num_vis_samples as i32,
+            samplingRate: simulation_settings.sampling_rate as i32,
+            frameSize: simulation_settings.frame_size as i32,
             openCLDevice: std::ptr::null_mut(),
             radeonRaysDevice: std::ptr::null_mut(),
             tanDevice: std::ptr::null_mut(),
@@ -158,6 +175,7 @@ impl Into<ffi::IPLSimulationSettings> for &SimulationSettings {
 pub struct Simulator {
     inner: ffi::IPLSimulator,
     settings: ffi::IPLSimulationSettings,
+    reflection_settings: ReflectionEffectSettings,
 }
 
 unsafe impl Send for Simulator {}
@@ -180,6 +198,7 @@ impl Simulator {
         let mut simulator = Self {
             inner: std::ptr::null_mut(),
             settings: ipl_settings,
+            reflection_settings: ReflectionEffectSettings::from_simulation_settings(settings),
         };
 
         unsafe {
@@ -194,6 +213,12 @@ impl Simulator {
         }
     }
 
+    /// Layout of the impulse responses this simulator generates, use it to
+    /// create the [`ReflectionEffect`]s and [`ReflectionMixer`]s they are applied with.
+    pub fn reflection_settings(&self) -> &ReflectionEffectSettings {
+        &self.reflection_settings
+    }
+
     pub fn commit(&mut self) {
         unsafe {
             ffi::iplSimulatorCommit(self.inner_raw());
@@ -253,6 +278,7 @@ impl Drop for Simulator {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimulationSharedInputs {
     pub listener: Orientation,
     pub num_rays: u32,
@@ -275,15 +301,15 @@ impl Default for SimulationSharedInputs {
     }
 }
 
-impl Into<ffi::IPLSimulationSharedInputs> for &SimulationSharedInputs {
-    fn into(self) -> ffi::IPLSimulationSharedInputs {
+impl From<&SimulationSharedInputs> for ffi::IPLSimulationSharedInputs {
+    fn from(simulation_shared_inputs: &SimulationSharedInputs) -> Self {
         ffi::IPLSimulationSharedInputs {
-            listener: self.listener.clone().into(),
-            numRays: self.num_rays as i32,
-            numBounces: self.num_bounces as i32,
-            duration: self.duration,
-            order: self.order as i32,
-            irradianceMinDistance: self.irradiance_min_distance,
+            listener: simulation_shared_inputs.listener.clone().into(),
+            numRays: simulation_shared_inputs.num_rays as i32,
+            numBounces: simulation_shared_inputs.num_bounces as i32,
+            duration: simulation_shared_inputs.duration,
+            order: simulation_shared_inputs.order as i32,
+            irradianceMinDistance: simulation_shared_inputs.irradiance_min_distance,
 
             // TODO: Expose these
             pathingUserData: std::ptr::null_mut(),
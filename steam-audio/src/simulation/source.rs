@@ -2,37 +2,34 @@ use crate::{prelude::*, Orientation};
 use steam_audio_sys::ffi;
 
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SourceSettings {
     pub flags: SimulationFlags,
 }
 
-impl Into<ffi::IPLSourceSettings> for &SourceSettings {
-    fn into(self) -> ffi::IPLSourceSettings {
+impl From<&SourceSettings> for ffi::IPLSourceSettings {
+    fn from(source_settings: &SourceSettings) -> Self {
         ffi::IPLSourceSettings {
-            flags: self.flags.into(),
+            flags: source_settings.flags.into(),
         }
     }
 }
 
+/// Results of the last simulation of a [`Source`], borrowing it for the
+/// impulse response in `reflections`.
 #[derive(Debug, Clone)]
-pub struct SimulationOutputs {
+pub struct SimulationOutputs<'a> {
     pub direct: DirectEffectParams,
+    pub reflections: ReflectionEffectParams<'a>,
     /*
-    pub reflections: ReflectionEffectParams,
     pub pathing: PathEffectParams,
     */
 }
 
-impl From<ffi::IPLSimulationOutputs> for SimulationOutputs {
-    fn from(other: ffi::IPLSimulationOutputs) -> Self {
-        Self {
-            direct: other.direct.into(),
-        }
-    }
-}
-
 pub struct Source {
     inner: ffi::IPLSource,
+    // Layout of the impulse responses written by the simulator.
+    reflection_settings: ReflectionEffectSettings,
 }
 
 unsafe impl Send for Source {}
@@ -53,6 +50,7 @@ impl Source {
     pub fn new(simulator: &Simulator, settings: &SourceSettings) -> Result<Self, SteamAudioError> {
         let mut source = Self {
             inner: std::ptr::null_mut(),
+            reflection_settings: *simulator.reflection_settings(),
         };
 
         let mut ipl_settings: ffi::IPLSourceSettings = settings.into();
@@ -66,11 +64,20 @@ impl Source {
         }
     }
 
-    pub fn get_outputs(&self, flags: SimulationFlags) -> SimulationOutputs {
-        unsafe {
+    pub fn get_outputs(&self, flags: SimulationFlags) -> SimulationOutputs<'_> {
+        let outputs = unsafe {
             let mut outputs: ffi::IPLSimulationOutputs = std::mem::zeroed();
             ffi::iplSourceGetOutputs(self.inner_raw(), flags.into(), &mut outputs);
-            outputs.into()
+            outputs
+        };
+
+        SimulationOutputs {
+            direct: outputs.direct.into(),
+            reflections: ReflectionEffectParams::from_source(
+                self,
+                &self.reflection_settings,
+                outputs.reflections,
+            ),
         }
     }
 
@@ -94,20 +101,18 @@ pub trait DistanceAttenuationCallback {
     fn attenuation(&self, distance: f32) -> f32;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DistanceAttenuationModel {
+    #[default]
     Default,
-    InverseDistance { min_distance: f32 },
-}
-
-impl Default for DistanceAttenuationModel {
-    fn default() -> Self {
-        Self::Default
-    }
+    InverseDistance {
+        min_distance: f32,
+    },
 }
 
-impl Into<ffi::IPLDistanceAttenuationModel> for DistanceAttenuationModel {
-    fn into(self) -> ffi::IPLDistanceAttenuationModel {
+impl From<DistanceAttenuationModel> for ffi::IPLDistanceAttenuationModel {
+    fn from(distance_attenuation_model: DistanceAttenuationModel) -> Self {
         let mut model = ffi::IPLDistanceAttenuationModel {
             type_: ffi::IPLDistanceAttenuationModelType::IPL_DISTANCEATTENUATIONTYPE_DEFAULT,
             minDistance: 1.0,
@@ -116,12 +121,12 @@ impl Into<ffi::IPLDistanceAttenuationModel> for DistanceAttenuationModel {
             dirty: false.into(),
         };
 
-        match self {
-            Self::Default => {
+        match distance_attenuation_model {
+            DistanceAttenuationModel::Default => {
                 model.type_ =
                     ffi::IPLDistanceAttenuationModelType::IPL_DISTANCEATTENUATIONTYPE_DEFAULT;
             }
-            Self::InverseDistance { min_distance } => {
+            DistanceAttenuationModel::InverseDistance { min_distance } => {
                 model.type_ = ffi::IPLDistanceAttenuationModelType::IPL_DISTANCEATTENUATIONTYPE_INVERSEDISTANCE;
                 model.minDistance = min_distance;
             }
@@ -135,8 +140,8 @@ impl DistanceAttenuationModel {
     pub fn calculate(
         &self,
         context: &Context,
-        source_position: [f32; 3],
-        listener_position: [f32; 3],
+        source_position: impl Into<[f32; 3]>,
+        listener_position: impl Into<[f32; 3]>,
     ) -> f32 {
         let mut model: ffi::IPLDistanceAttenuationModel = (*self).clone().into();
         let model_pointer = &mut model as *mut _;
@@ -144,28 +149,26 @@ impl DistanceAttenuationModel {
         unsafe {
             ffi::iplDistanceAttenuationCalculate(
                 context.inner_raw(),
-                source_position.into(),
-                listener_position.into(),
+                source_position.into().into(),
+                listener_position.into().into(),
                 model_pointer,
             )
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AirAbsorptionModel {
+    #[default]
     Default,
-    Exponential { coefficients: [f32; 3] },
-}
-
-impl Default for AirAbsorptionModel {
-    fn default() -> Self {
-        Self::Default
-    }
+    Exponential {
+        coefficients: [f32; 3],
+    },
 }
 
-impl Into<ffi::IPLAirAbsorptionModel> for AirAbsorptionModel {
-    fn into(self) -> ffi::IPLAirAbsorptionModel {
+impl From<AirAbsorptionModel> for ffi::IPLAirAbsorptionModel {
+    fn from(air_absorption_model: AirAbsorptionModel) -> Self {
         let mut model = ffi::IPLAirAbsorptionModel {
             type_: ffi::IPLAirAbsorptionModelType::IPL_AIRABSORPTIONTYPE_DEFAULT,
             coefficients: [0.0, 0.0, 0.0],
@@ -174,11 +177,11 @@ impl Into<ffi::IPLAirAbsorptionModel> for AirAbsorptionModel {
             dirty: false.into(),
         };
 
-        match self {
-            Self::Default => {
+        match air_absorption_model {
+            AirAbsorptionModel::Default => {
                 model.type_ = ffi::IPLAirAbsorptionModelType::IPL_AIRABSORPTIONTYPE_DEFAULT;
             }
-            Self::Exponential { coefficients } => {
+            AirAbsorptionModel::Exponential { coefficients } => {
                 model.type_ = ffi::IPLAirAbsorptionModelType::IPL_AIRABSORPTIONTYPE_EXPONENTIAL;
                 model.coefficients = coefficients;
             }
@@ -192,8 +195,8 @@ impl AirAbsorptionModel {
     pub fn calculate(
         &self,
         context: &Context,
-        source_position: [f32; 3],
-        listener_position: [f32; 3],
+        source_position: impl Into<[f32; 3]>,
+        listener_position: impl Into<[f32; 3]>,
     ) -> [f32; 3] {
         let mut model: ffi::IPLAirAbsorptionModel = (*self).clone().into();
         let model_pointer = &mut model as *mut _;
@@ -203,8 +206,8 @@ impl AirAbsorptionModel {
         unsafe {
             ffi::iplAirAbsorptionCalculate(
                 context.inner_raw(),
-                source_position.into(),
-                listener_position.into(),
+                source_position.into().into(),
+                listener_position.into().into(),
                 model_pointer,
                 air_absorption.as_mut_ptr(),
             )
@@ -215,6 +218,7 @@ impl AirAbsorptionModel {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Directivity {
     pub dipole_weight: f32,
     pub dipole_power: f32,
@@ -229,11 +233,11 @@ impl Default for Directivity {
     }
 }
 
-impl Into<ffi::IPLDirectivity> for Directivity {
-    fn into(self) -> ffi::IPLDirectivity {
+impl From<Directivity> for ffi::IPLDirectivity {
+    fn from(directivity: Directivity) -> Self {
         ffi::IPLDirectivity {
-            dipolePower: self.dipole_power,
-            dipoleWeight: self.dipole_weight,
+            dipolePower: directivity.dipole_power,
+            dipoleWeight: directivity.dipole_weight,
             callback: None,
             userData: std::ptr::null_mut(),
         }
@@ -244,8 +248,8 @@ impl Directivity {
     pub fn calculate(
         &self,
         context: &Context,
-        source_transform: Orientation,
-        listener_position: [f32; 3],
+        source_transform: impl Into<Orientation>,
+        listener_position: impl Into<[f32; 3]>,
     ) -> f32 {
         let mut model: ffi::IPLDirectivity = (*self).clone().into();
         let model_pointer = &mut model as *mut _;
@@ -253,16 +257,18 @@ impl Directivity {
         unsafe {
             ffi::iplDirectivityCalculate(
                 context.inner_raw(),
-                source_transform.into(),
-                listener_position.into(),
+                source_transform.into().into(),
+                listener_position.into().into(),
                 model_pointer,
             )
         }
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OcclusionType {
+    #[default]
     Raycast,
     Volumetric {
         occlusion_radius: f32,
@@ -270,22 +276,17 @@ pub enum OcclusionType {
     },
 }
 
-impl Default for OcclusionType {
-    fn default() -> Self {
-        Self::Raycast
-    }
-}
-
-impl Into<ffi::IPLOcclusionType> for OcclusionType {
-    fn into(self) -> ffi::IPLOcclusionType {
-        match self {
-            Self::Raycast => ffi::IPLOcclusionType::IPL_OCCLUSIONTYPE_RAYCAST,
-            Self::Volumetric { .. } => ffi::IPLOcclusionType::IPL_OCCLUSIONTYPE_VOLUMETRIC,
+impl From<OcclusionType> for ffi::IPLOcclusionType {
+    fn from(occlusion_type: OcclusionType) -> Self {
+        match occlusion_type {
+            OcclusionType::Raycast => ffi::IPLOcclusionType::IPL_OCCLUSIONTYPE_RAYCAST,
+            OcclusionType::Volumetric { .. } => ffi::IPLOcclusionType::IPL_OCCLUSIONTYPE_VOLUMETRIC,
         }
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimulationInputs {
     pub flags: SimulationFlags,
     pub direct_flags: DirectSimulationFlags,
@@ -332,35 +333,33 @@ impl Default for SimulationInputs {
     }
 }
 
-impl Into<ffi::IPLSimulationInputs> for &SimulationInputs {
-    fn into(self) -> ffi::IPLSimulationInputs {
+impl From<&SimulationInputs> for ffi::IPLSimulationInputs {
+    fn from(simulation_inputs: &SimulationInputs) -> Self {
         let mut ffi_occlusion_radius = 0.0;
         let mut ffi_num_occlusion_samples = 0;
-        match self.occlusion_type {
-            OcclusionType::Volumetric {
-                occlusion_radius,
-                num_occlusion_samples,
-            } => {
-                ffi_occlusion_radius = occlusion_radius;
-                ffi_num_occlusion_samples = num_occlusion_samples;
-            }
-            _ => {}
+        if let OcclusionType::Volumetric {
+            occlusion_radius,
+            num_occlusion_samples,
+        } = simulation_inputs.occlusion_type
+        {
+            ffi_occlusion_radius = occlusion_radius;
+            ffi_num_occlusion_samples = num_occlusion_samples;
         }
 
         ffi::IPLSimulationInputs {
-            flags: self.flags.into(),
-            directFlags: self.direct_flags.into(),
-            source: self.source.clone().into(),
-            distanceAttenuationModel: self.distance_attenuation_model.clone().into(),
-            airAbsorptionModel: self.air_absorption_model.clone().into(),
-            directivity: self.directivity.clone().into(),
-            occlusionType: self.occlusion_type.into(),
+            flags: simulation_inputs.flags.into(),
+            directFlags: simulation_inputs.direct_flags.into(),
+            source: simulation_inputs.source.clone().into(),
+            distanceAttenuationModel: simulation_inputs.distance_attenuation_model.clone().into(),
+            airAbsorptionModel: simulation_inputs.air_absorption_model.clone().into(),
+            directivity: simulation_inputs.directivity.clone().into(),
+            occlusionType: simulation_inputs.occlusion_type.into(),
             occlusionRadius: ffi_occlusion_radius,
             numOcclusionSamples: ffi_num_occlusion_samples as i32,
-            reverbScale: self.reverb_scale,
-            hybridReverbTransitionTime: self.hybrid_reverb_transition_time,
-            hybridReverbOverlapPercent: self.hybrid_reverb_overlap_percent,
-            baked: self.baked.into(),
+            reverbScale: simulation_inputs.reverb_scale,
+            hybridReverbTransitionTime: simulation_inputs.hybrid_reverb_transition_time,
+            hybridReverbOverlapPercent: simulation_inputs.hybrid_reverb_overlap_percent,
+            baked: simulation_inputs.baked.into(),
             bakedDataIdentifier: ffi::IPLBakedDataIdentifier {
                 type_: ffi::IPLBakedDataType::IPL_BAKEDDATATYPE_PATHING,
                 variation: ffi::IPLBakedDataVariation::IPL_BAKEDDATAVARIATION_DYNAMIC,
@@ -370,12 +369,12 @@ impl Into<ffi::IPLSimulationInputs> for &SimulationInputs {
                 },
             },
             pathingProbes: std::ptr::null_mut(),
-            visRadius: self.visible_radius,
-            visThreshold: self.visible_threshold,
-            visRange: self.visible_range,
-            pathingOrder: self.pathing_order as i32,
-            enableValidation: self.enabled_validation.into(),
-            findAlternatePaths: self.find_alternate_paths.into(),
+            visRadius: simulation_inputs.visible_radius,
+            visThreshold: simulation_inputs.visible_threshold,
+            visRange: simulation_inputs.visible_range,
+            pathingOrder: simulation_inputs.pathing_order as i32,
+            enableValidation: simulation_inputs.enabled_validation.into(),
+            findAlternatePaths: simulation_inputs.find_alternate_paths.into(),
             numTransmissionRays: 8,
         }
     }
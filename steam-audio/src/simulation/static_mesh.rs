@@ -10,17 +10,148 @@ pub struct StaticMeshSettings {
     pub material_indices: Vec<i32>,
 }
 
-impl Into<StoredStaticMeshSettings> for StaticMeshSettings {
-    fn into(self) -> StoredStaticMeshSettings {
+impl StaticMeshSettings {
+    /// Check that every index is in bounds and every triangle is usable, steam
+    /// audio reads out of bounds or produces garbage otherwise.
+    ///
+    /// Called by [`StaticMesh::new`], reports the first problem found.
+    pub fn validate(&self) -> Result<(), SteamAudioError> {
+        self.validate_mesh().map_err(SteamAudioError::InvalidMesh)
+    }
+
+    fn validate_mesh(&self) -> Result<(), MeshError> {
+        if self.material_indices.len() != self.triangles.len() {
+            return Err(MeshError::MaterialIndexCount {
+                triangles: self.triangles.len(),
+                material_indices: self.material_indices.len(),
+            });
+        }
+
+        for (triangle, (indices, &material)) in self
+            .triangles
+            .iter()
+            .zip(&self.material_indices)
+            .enumerate()
+        {
+            let mut corners = [[0.0; 3]; 3];
+            for (corner, &vertex) in corners.iter_mut().zip(indices) {
+                *corner = usize::try_from(vertex)
+                    .ok()
+                    .and_then(|vertex| self.vertices.get(vertex))
+                    .copied()
+                    .ok_or(MeshError::VertexOutOfRange { triangle, vertex })?;
+
+                if corner.iter().any(|axis| !axis.is_finite()) {
+                    return Err(MeshError::NonFiniteVertex { triangle, vertex });
+                }
+            }
+
+            if material < 0 || material as usize >= self.materials.len() {
+                return Err(MeshError::MaterialOutOfRange { triangle, material });
+            }
+
+            let [a, b, c] = corners;
+            let (ab, ac) = (sub(b, a), sub(c, a));
+            let normal = [
+                ab[1] * ac[2] - ab[2] * ac[1],
+                ab[2] * ac[0] - ab[0] * ac[2],
+                ab[0] * ac[1] - ab[1] * ac[0],
+            ];
+            if normal.iter().all(|axis| *axis == 0.0) {
+                return Err(MeshError::DegenerateTriangle { triangle });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+/// What is wrong with a [`StaticMeshSettings`], `triangle` is the index of the
+/// offending triangle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MeshError {
+    /// Every triangle needs exactly one material index.
+    MaterialIndexCount {
+        triangles: usize,
+        material_indices: usize,
+    },
+    VertexOutOfRange {
+        triangle: usize,
+        vertex: i32,
+    },
+    MaterialOutOfRange {
+        triangle: usize,
+        material: i32,
+    },
+    /// A vertex of the triangle has a NaN or infinite coordinate.
+    NonFiniteVertex {
+        triangle: usize,
+        vertex: i32,
+    },
+    /// The triangle has zero area.
+    DegenerateTriangle {
+        triangle: usize,
+    },
+}
+
+impl std::fmt::Display for MeshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MaterialIndexCount {
+                triangles,
+                material_indices,
+            } => write!(
+                f,
+                "{} material indices for {} triangles",
+                material_indices, triangles
+            ),
+            Self::VertexOutOfRange { triangle, vertex } => write!(
+                f,
+                "triangle {} uses vertex {} which doesn't exist",
+                triangle, vertex
+            ),
+            Self::MaterialOutOfRange { triangle, material } => write!(
+                f,
+                "triangle {} uses material {} which doesn't exist",
+                triangle, material
+            ),
+            Self::NonFiniteVertex { triangle, vertex } => write!(
+                f,
+                "triangle {} uses vertex {} which isn't finite",
+                triangle, vertex
+            ),
+            Self::DegenerateTriangle { triangle } => {
+                write!(f, "triangle {} has zero area", triangle)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+impl From<StaticMeshSettings> for StoredStaticMeshSettings {
+    fn from(static_mesh_settings: StaticMeshSettings) -> Self {
         StoredStaticMeshSettings {
-            vertices: self.vertices.iter().map(|v| v.into()).collect(),
-            triangles: self
+            vertices: static_mesh_settings
+                .vertices
+                .iter()
+                .map(|v| v.into())
+                .collect(),
+            triangles: static_mesh_settings
                 .triangles
                 .iter()
                 .map(|arr| ffi::IPLTriangle { indices: *arr })
                 .collect(),
-            materials: self.materials.iter().map(|m| m.into()).collect(),
-            material_indices: self.material_indices,
+            materials: static_mesh_settings
+                .materials
+                .iter()
+                .map(|m| m.into())
+                .collect(),
+            material_indices: static_mesh_settings.material_indices,
         }
     }
 }
@@ -33,16 +164,16 @@ struct StoredStaticMeshSettings {
     material_indices: Vec<i32>,
 }
 
-impl Into<ffi::IPLStaticMeshSettings> for &mut StoredStaticMeshSettings {
-    fn into(self) -> ffi::IPLStaticMeshSettings {
+impl From<&mut StoredStaticMeshSettings> for ffi::IPLStaticMeshSettings {
+    fn from(stored_static_mesh_settings: &mut StoredStaticMeshSettings) -> Self {
         ffi::IPLStaticMeshSettings {
-            numVertices: self.vertices.len() as i32,
-            numTriangles: self.triangles.len() as i32,
-            numMaterials: self.materials.len() as i32,
-            vertices: self.vertices.as_mut_ptr(),
-            triangles: self.triangles.as_mut_ptr(),
-            materials: self.materials.as_mut_ptr(),
-            materialIndices: self.material_indices.as_mut_ptr(),
+            numVertices: stored_static_mesh_settings.vertices.len() as i32,
+            numTriangles: stored_static_mesh_settings.triangles.len() as i32,
+            numMaterials: stored_static_mesh_settings.materials.len() as i32,
+            vertices: stored_static_mesh_settings.vertices.as_mut_ptr(),
+            triangles: stored_static_mesh_settings.triangles.as_mut_ptr(),
+            materials: stored_static_mesh_settings.materials.as_mut_ptr(),
+            materialIndices: stored_static_mesh_settings.material_indices.as_mut_ptr(),
         }
     }
 }
@@ -72,6 +203,8 @@ impl crate::SteamAudioObject for StaticMesh {
 
 impl StaticMesh {
     pub fn new(scene: &Scene, settings: StaticMeshSettings) -> Result<Self, SteamAudioError> {
+        settings.validate()?;
+
         let mut mesh = Self {
             inner: std::ptr::null_mut(),
             settings: settings.into(),
@@ -87,6 +220,9 @@ impl StaticMesh {
         }
     }
 
+    /// # Safety
+    ///
+    /// The handle is only valid while this mesh is alive.
     pub unsafe fn inner(&self) -> ffi::IPLStaticMesh {
         self.inner
     }
@@ -99,3 +235,93 @@ impl Drop for StaticMesh {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials;
+
+    fn quad() -> StaticMeshSettings {
+        StaticMeshSettings {
+            vertices: vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+            ],
+            triangles: vec![[0, 1, 2], [0, 2, 3]],
+            materials: vec![materials::GENERIC],
+            material_indices: vec![0, 0],
+        }
+    }
+
+    fn mesh_error(settings: &StaticMeshSettings) -> Option<MeshError> {
+        settings.validate_mesh().err()
+    }
+
+    #[test]
+    fn accepts_valid_mesh() {
+        assert!(quad().validate().is_ok());
+    }
+
+    #[test]
+    fn mesh_error_is_the_source() {
+        use std::error::Error;
+
+        let mut settings = quad();
+        settings.triangles[0] = [0, 0, 1];
+        let error = settings.validate().unwrap_err();
+        let source = error.source().unwrap().downcast_ref::<MeshError>();
+        assert_eq!(source, Some(&MeshError::DegenerateTriangle { triangle: 0 }));
+    }
+
+    #[test]
+    fn reports_offending_triangle() {
+        let mut settings = quad();
+        settings.triangles[1] = [0, 2, 4];
+        assert_eq!(
+            mesh_error(&settings),
+            Some(MeshError::VertexOutOfRange {
+                triangle: 1,
+                vertex: 4
+            })
+        );
+
+        let mut settings = quad();
+        settings.material_indices[1] = 1;
+        assert_eq!(
+            mesh_error(&settings),
+            Some(MeshError::MaterialOutOfRange {
+                triangle: 1,
+                material: 1
+            })
+        );
+
+        let mut settings = quad();
+        settings.material_indices.pop();
+        assert_eq!(
+            mesh_error(&settings),
+            Some(MeshError::MaterialIndexCount {
+                triangles: 2,
+                material_indices: 1
+            })
+        );
+
+        let mut settings = quad();
+        settings.vertices[3][2] = f32::NAN;
+        assert_eq!(
+            mesh_error(&settings),
+            Some(MeshError::NonFiniteVertex {
+                triangle: 1,
+                vertex: 3
+            })
+        );
+
+        let mut settings = quad();
+        settings.vertices[2] = [2.0, 0.0, 0.0];
+        assert_eq!(
+            mesh_error(&settings),
+            Some(MeshError::DegenerateTriangle { triangle: 0 })
+        );
+    }
+}
@@ -0,0 +1,574 @@
+use std::sync::atomic::{self, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::audio_buffer::{Filled, MAX_CHANNELS};
+use crate::effect::direct::TransmissionType;
+use crate::logging::{self, LogLevel};
+use crate::prelude::*;
+use crate::resample::Resampler;
+use crate::simulation::source::DistanceAttenuationModel;
+use crate::stream::{FrameStream, ProcessFrame};
+use crate::Orientation;
+
+const LISTENER: usize = 0;
+const SOURCE: usize = LISTENER + 12;
+const DIRECT: usize = SOURCE + 3;
+const WORDS: usize = DIRECT + 12;
+
+/// Everything a [`SpatialHandle`] can change, flattened into words so it can be
+/// shared through atomics.
+#[derive(Debug, Clone)]
+struct SpatialState {
+    listener: Orientation,
+    source_position: [f32; 3],
+    direct: Option<DirectEffectParams>,
+}
+
+impl Default for SpatialState {
+    fn default() -> Self {
+        Self {
+            listener: Orientation::default(),
+            // Slightly ahead of the listener so there is a direction to render from.
+            source_position: [0.0, 0.0, -1.0],
+            direct: None,
+        }
+    }
+}
+
+fn encode_listener(listener: &Orientation) -> [u32; 12] {
+    let mut words = [0; 12];
+    let vectors = [listener.right, listener.up, listener.ahead, listener.origin];
+    for (word, value) in words.iter_mut().zip(vectors.iter().flatten()) {
+        *word = value.to_bits();
+    }
+    words
+}
+
+fn encode_vector(vector: [f32; 3]) -> [u32; 3] {
+    vector.map(f32::to_bits)
+}
+
+fn encode_direct(direct: Option<&DirectEffectParams>) -> [u32; 12] {
+    let mut words = [0; 12];
+    if let Some(direct) = direct {
+        words[0] = 1;
+        words[1] = direct.flags.bits() as u32;
+        words[2] = match direct.transmission_type {
+            TransmissionType::FrequencyIndependent => 0,
+            TransmissionType::FrequencyDependent => 1,
+        };
+        words[3] = direct.distance_attenuation.to_bits();
+        words[4] = direct.directivity.to_bits();
+        words[5] = direct.occlusion.to_bits();
+        words[6..9].copy_from_slice(&encode_vector(direct.air_absorption));
+        words[9..12].copy_from_slice(&encode_vector(direct.transmission));
+    }
+    words
+}
+
+impl SpatialState {
+    fn encode(&self) -> [u32; WORDS] {
+        let mut words = [0; WORDS];
+        words[LISTENER..SOURCE].copy_from_slice(&encode_listener(&self.listener));
+        words[SOURCE..DIRECT].copy_from_slice(&encode_vector(self.source_position));
+        words[DIRECT..WORDS].copy_from_slice(&encode_direct(self.direct.as_ref()));
+        words
+    }
+
+    fn decode(words: &[u32; WORDS]) -> Self {
+        let float = |index: usize| f32::from_bits(words[index]);
+        let vector = |index: usize| [float(index), float(index + 1), float(index + 2)];
+
+        let direct = if words[DIRECT] != 0 {
+            Some(DirectEffectParams {
+                flags: DirectEffectFlags::from_bits_truncate(words[DIRECT + 1] as i32),
+                transmission_type: match words[DIRECT + 2] {
+                    0 => TransmissionType::FrequencyIndependent,
+                    _ => TransmissionType::FrequencyDependent,
+                },
+                distance_attenuation: float(DIRECT + 3),
+                directivity: float(DIRECT + 4),
+                occlusion: float(DIRECT + 5),
+                air_absorption: vector(DIRECT + 6),
+                transmission: vector(DIRECT + 9),
+            })
+        } else {
+            None
+        };
+
+        Self {
+            listener: Orientation {
+                right: vector(LISTENER),
+                up: vector(LISTENER + 3),
+                ahead: vector(LISTENER + 6),
+                origin: vector(LISTENER + 9),
+            },
+            source_position: vector(SOURCE),
+            direct,
+        }
+    }
+}
+
+/// Sequence lock over the encoded [`SpatialState`].
+///
+/// Readers never block, if a write is in progress they keep using their last
+/// snapshot. Writers only spin against other writers.
+struct SharedState {
+    sequence: AtomicU32,
+    words: [AtomicU32; WORDS],
+}
+
+impl SharedState {
+    fn new(state: &SpatialState) -> Self {
+        Self {
+            sequence: AtomicU32::new(0),
+            words: state.encode().map(AtomicU32::new),
+        }
+    }
+
+    fn write(&self, offset: usize, values: &[u32]) {
+        loop {
+            let sequence = self.sequence.load(Ordering::Relaxed);
+            if sequence.is_multiple_of(2)
+                && self
+                    .sequence
+                    .compare_exchange_weak(
+                        sequence,
+                        sequence.wrapping_add(1),
+                        Ordering::Acquire,
+                        Ordering::Relaxed,
+                    )
+                    .is_ok()
+            {
+                break;
+            }
+
+            std::hint::spin_loop();
+        }
+        atomic::fence(Ordering::Release);
+
+        for (word, value) in self.words[offset..].iter().zip(values) {
+            word.store(*value, Ordering::Relaxed);
+        }
+
+        self.sequence.fetch_add(1, Ordering::Release);
+    }
+
+    fn try_read(&self) -> Option<[u32; WORDS]> {
+        let before = self.sequence.load(Ordering::Acquire);
+        if before % 2 == 1 {
+            return None;
+        }
+
+        let mut words = [0; WORDS];
+        for (value, word) in words.iter_mut().zip(self.words.iter()) {
+            *value = word.load(Ordering::Relaxed);
+        }
+
+        atomic::fence(Ordering::Acquire);
+        if self.sequence.load(Ordering::Relaxed) == before {
+            Some(words)
+        } else {
+            None
+        }
+    }
+}
+
+/// Updates the positions used by a [`Spatial`] source from any thread.
+///
+/// Cheap to clone, updates never block the audio thread and are picked up at
+/// the start of the next frame.
+#[derive(Clone)]
+pub struct SpatialHandle {
+    shared: Arc<SharedState>,
+    error: Arc<Mutex<Option<SteamAudioError>>>,
+}
+
+impl SpatialHandle {
+    pub fn set_listener(&self, listener: &Orientation) {
+        self.shared.write(LISTENER, &encode_listener(listener));
+    }
+
+    pub fn set_source_position(&self, position: impl Into<[f32; 3]>) {
+        self.shared.write(SOURCE, &encode_vector(position.into()));
+    }
+
+    /// Use simulated direct parameters, e.g. [`SimulationOutputs::direct`].
+    ///
+    /// With `None` only distance attenuation is applied, calculated from the
+    /// source and listener positions.
+    ///
+    /// [`SimulationOutputs::direct`]: crate::simulation::source::SimulationOutputs::direct
+    pub fn set_direct_params(&self, params: Option<&DirectEffectParams>) {
+        self.shared.write(DIRECT, &encode_direct(params));
+    }
+
+    /// The error that ended playback, if processing failed.
+    pub fn take_error(&self) -> Option<SteamAudioError> {
+        self.error
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take()
+    }
+}
+
+/// Renders a source through [`DirectEffect`] and [`BinauralEffect`].
+struct SpatialProcessor<S> {
+    source: S,
+    context: Context,
+    direct_effect: DirectEffect,
+    binaural_effect: BinauralEffect,
+    distance_attenuation_model: DistanceAttenuationModel,
+
+    // Holds `MAX_CHANNELS` worth of samples so layout changes don't allocate.
+    input: DeinterleavedFrame,
+    mono: DeinterleavedFrame,
+    direct_output: DeinterleavedFrame,
+
+    shared: Arc<SharedState>,
+    state: SpatialState,
+    error: Arc<Mutex<Option<SteamAudioError>>>,
+}
+
+impl<S> ProcessFrame for SpatialProcessor<S>
+where
+    S: rodio::Source + Iterator,
+    S::Item: rodio::Sample,
+{
+    fn process_frame(&mut self, output: &mut DeinterleavedFrame) -> Filled {
+        match self.try_process_frame(output) {
+            Ok(filled) => filled,
+            Err(error) => {
+                logging::dispatch(
+                    LogLevel::Error,
+                    "spatial source stopped, see SpatialHandle::take_error",
+                );
+                // Never block the audio thread, the failure was logged either way.
+                if let Ok(mut slot) = self.error.try_lock() {
+                    *slot = Some(error);
+                }
+                Filled::Empty
+            }
+        }
+    }
+}
+
+impl<S> SpatialProcessor<S>
+where
+    S: rodio::Source + Iterator,
+    S::Item: rodio::Sample,
+{
+    fn try_process_frame(
+        &mut self,
+        output: &mut DeinterleavedFrame,
+    ) -> Result<Filled, SteamAudioError> {
+        // Sources like rodio's queue can switch channel layouts between sounds,
+        // `push_source` stops at the switch so it always lands on a frame boundary.
+        let channels = self.source.channels();
+        if channels != self.input.channels() {
+            if channels as usize > MAX_CHANNELS {
+                return Err(SteamAudioError::ChannelMismatch {
+                    expected: MAX_CHANNELS,
+                    got: channels as usize,
+                });
+            }
+            self.input.set_channels(channels);
+        }
+
+        let (mut filled, layout_changed) = self.input.push_source_span(&mut self.source)?;
+        if layout_changed {
+            // The source goes on, play the frame padded with silence and
+            // switch on the next one.
+            filled = Filled::Full;
+        } else if filled == Filled::Empty {
+            return Ok(filled);
+        }
+
+        if let Some(words) = self.shared.try_read() {
+            self.state = SpatialState::decode(&words);
+        }
+
+        let mono = if self.input.channels() == 1 {
+            &mut self.input
+        } else {
+            self.input.downmix_to_mono(&self.context, &mut self.mono)?;
+            &mut self.mono
+        };
+
+        let direct_params = match &self.state.direct {
+            Some(direct) => direct.clone(),
+            None => DirectEffectParams {
+                distance_attenuation: self.distance_attenuation_model.calculate(
+                    &self.context,
+                    self.state.source_position,
+                    self.state.listener.origin,
+                ),
+                ..Default::default()
+            },
+        };
+
+        let mut binaural_params = BinauralParams::from_positions(
+            &self.context,
+            &self.state.listener,
+            self.state.source_position,
+        );
+        binaural_params.interpolation = HRTFInterpolation::Bilinear;
+
+        self.direct_effect
+            .apply_to_buffer(&direct_params, mono, &mut self.direct_output)?;
+        self.binaural_effect
+            .apply_to_buffer(&binaural_params, &mut self.direct_output, output)?;
+
+        Ok(filled)
+    }
+}
+
+/// Spatializes a rodio source, the HRTF equivalent of `rodio::SpatialSink`.
+///
+/// Yields stereo samples at [`AudioSettings::sampling_rate`], sources at any
+/// other rate have to go through [`Spatial::resampled`].
+///
+/// If processing fails the stream ends, the error is logged and kept for
+/// [`SpatialHandle::take_error`].
+///
+/// ```ignore
+/// let spatial = Spatial::new(&context, &audio_settings, &hrtf, decoder)?;
+/// let handle = spatial.handle();
+/// sink.append(spatial);
+///
+/// // Later, from the game thread.
+/// handle.set_source_position([2.0, 0.0, 0.0]);
+/// ```
+pub struct Spatial<S> {
+    stream: FrameStream<SpatialProcessor<S>>,
+    handle: SpatialHandle,
+}
+
+impl<S> Spatial<S>
+where
+    S: rodio::Source + Iterator,
+    S::Item: rodio::Sample,
+{
+    pub fn new(
+        context: &Context,
+        audio_settings: &AudioSettings,
+        hrtf: &HRTF,
+        source: S,
+    ) -> Result<Self, SteamAudioError> {
+        audio_settings.check_sample_rate(source.sample_rate())?;
+
+        let frame_size = audio_settings.frame_size() as usize;
+        let sample_rate = audio_settings.sampling_rate();
+        let state = SpatialState::default();
+        let shared = Arc::new(SharedState::new(&state));
+        let error = Arc::new(Mutex::new(None));
+
+        let mut input = DeinterleavedFrame::new(frame_size, MAX_CHANNELS as u16, sample_rate);
+        input.set_channels(source.channels().min(MAX_CHANNELS as u16));
+
+        let processor = SpatialProcessor {
+            input,
+            mono: DeinterleavedFrame::new(frame_size, 1, sample_rate),
+            direct_output: DeinterleavedFrame::new(frame_size, 1, sample_rate),
+            source,
+            context: context.retain(),
+            direct_effect: DirectEffect::new(context, audio_settings, 1)?,
+            binaural_effect: BinauralEffect::new(context, audio_settings, hrtf)?,
+            distance_attenuation_model: DistanceAttenuationModel::default(),
+            shared: shared.clone(),
+            state,
+            error: error.clone(),
+        };
+
+        Ok(Self {
+            stream: FrameStream::new(frame_size, 2, sample_rate, processor),
+            handle: SpatialHandle { shared, error },
+        })
+    }
+
+    /// Like [`Spatial::new`] but resamples `source` if its sample rate doesn't
+    /// match `audio_settings`.
+    pub fn resampled(
+        context: &Context,
+        audio_settings: &AudioSettings,
+        hrtf: &HRTF,
+        source: S,
+    ) -> Result<Spatial<Resampler<S>>, SteamAudioError> {
+        let source = Resampler::for_audio_settings(source, audio_settings)?;
+        Spatial::new(context, audio_settings, hrtf, source)
+    }
+
+    pub fn with_distance_attenuation_model(mut self, model: DistanceAttenuationModel) -> Self {
+        self.stream.processor_mut().distance_attenuation_model = model;
+        self
+    }
+
+    pub fn handle(&self) -> SpatialHandle {
+        self.handle.clone()
+    }
+}
+
+impl<S> Iterator for Spatial<S>
+where
+    S: rodio::Source + Iterator,
+    S::Item: rodio::Sample,
+{
+    type Item = f32;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stream.next()
+    }
+}
+
+impl<S> rodio::Source for Spatial<S>
+where
+    S: rodio::Source + Iterator,
+    S::Item: rodio::Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.stream.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.stream.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.stream.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        self.stream.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handle_updates_round_trip() {
+        let shared = Arc::new(SharedState::new(&SpatialState::default()));
+        let handle = SpatialHandle {
+            shared: shared.clone(),
+            error: Arc::new(Mutex::new(None)),
+        };
+
+        let listener = Orientation {
+            origin: [1.0, 2.0, 3.0],
+            ..Default::default()
+        };
+        let direct = DirectEffectParams {
+            flags: DirectEffectFlags::OCCLUSION | DirectEffectFlags::TRANSMISSION,
+            transmission_type: TransmissionType::FrequencyDependent,
+            occlusion: 0.25,
+            transmission: [0.1, 0.2, 0.3],
+            ..Default::default()
+        };
+
+        handle.set_listener(&listener);
+        handle.set_source_position([4.0, 5.0, 6.0]);
+        handle.set_direct_params(Some(&direct));
+
+        let state = SpatialState::decode(&shared.try_read().unwrap());
+        assert_eq!(state.listener.origin, listener.origin);
+        assert_eq!(state.listener.ahead, listener.ahead);
+        assert_eq!(state.source_position, [4.0, 5.0, 6.0]);
+
+        let decoded = state.direct.unwrap();
+        assert_eq!(decoded.flags, direct.flags);
+        assert_eq!(decoded.occlusion, 0.25);
+        assert_eq!(decoded.transmission, [0.1, 0.2, 0.3]);
+
+        handle.set_direct_params(None);
+        let state = SpatialState::decode(&shared.try_read().unwrap());
+        assert!(state.direct.is_none());
+    }
+
+    // Plays several buffers back to back, like rodio's queue.
+    struct Segments(std::collections::VecDeque<rodio::buffer::SamplesBuffer<f32>>);
+
+    impl Iterator for Segments {
+        type Item = f32;
+        fn next(&mut self) -> Option<f32> {
+            let segment = self.0.front_mut()?;
+            let sample = segment.next();
+            // Move on as soon as a segment is done so `channels` describes the next sample.
+            if segment.size_hint().0 == 0 {
+                self.0.pop_front();
+            }
+            sample
+        }
+    }
+
+    impl rodio::Source for Segments {
+        // Like rodio's queue, every segment ends a span.
+        fn current_frame_len(&self) -> Option<usize> {
+            Some(self.0.front().map_or(0, |segment| segment.size_hint().0))
+        }
+
+        fn channels(&self) -> u16 {
+            self.0.front().map_or(1, |segment| segment.channels())
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.0
+                .front()
+                .map_or(48000, |segment| segment.sample_rate())
+        }
+
+        fn total_duration(&self) -> Option<std::time::Duration> {
+            None
+        }
+    }
+
+    // Segments of `(channels, sample_rate, samples per channel)`.
+    fn spatial(segments: &[(u16, u32, usize)]) -> Spatial<Segments> {
+        let context = Context::new(&ContextSettings::default()).unwrap();
+        let audio_settings = AudioSettings::new(48000, 256).unwrap();
+        let hrtf = HRTF::new(&context, &audio_settings, &HRTFSettings::default()).unwrap();
+
+        let segments = segments
+            .iter()
+            .map(|&(channels, sample_rate, len)| {
+                let samples = vec![0.5; len * channels as usize];
+                rodio::buffer::SamplesBuffer::new(channels, sample_rate, samples)
+            })
+            .collect();
+        Spatial::new(&context, &audio_settings, &hrtf, Segments(segments)).unwrap()
+    }
+
+    #[test]
+    fn follows_channel_changes() {
+        let spatial = spatial(&[(2, 48000, 256), (1, 48000, 256)]);
+        let handle = spatial.handle();
+
+        assert_eq!(spatial.count(), 2 * 256 * 2);
+        assert!(handle.take_error().is_none());
+    }
+
+    #[test]
+    fn switches_channels_on_frame_boundaries() {
+        // The first frame ends early and is padded, the mono segment starts a new one.
+        let spatial = spatial(&[(2, 48000, 100), (1, 48000, 256)]);
+        let handle = spatial.handle();
+
+        assert_eq!(spatial.count(), 2 * 256 * 2);
+        assert!(handle.take_error().is_none());
+    }
+
+    #[test]
+    fn errors_end_the_stream_and_reach_the_handle() {
+        let spatial = spatial(&[(1, 48000, 256), (1, 44100, 256)]);
+        let handle = spatial.handle();
+
+        assert_eq!(spatial.count(), 256 * 2);
+        assert!(matches!(
+            handle.take_error(),
+            Some(SteamAudioError::SampleRateMismatch {
+                expected: 48000,
+                got: 44100
+            })
+        ));
+        assert!(handle.take_error().is_none());
+    }
+}
@@ -0,0 +1,67 @@
+use steam_audio_sys::ffi;
+
+/// Speaker arrangement used when panning to, or rendering from, multichannel audio.
+#[derive(Debug, Clone, Default)]
+pub enum SpeakerLayout {
+    Mono,
+    #[default]
+    Stereo,
+    Quadraphonic,
+    Surround5_1,
+    Surround7_1,
+    /// Unit-length directions for each speaker, one output channel per speaker.
+    Custom {
+        speakers: Vec<[f32; 3]>,
+    },
+}
+
+// The returned layout borrows the custom speaker directions, so it must not outlive `self`.
+impl From<&SpeakerLayout> for ffi::IPLSpeakerLayout {
+    fn from(speaker_layout: &SpeakerLayout) -> Self {
+        let mut layout = ffi::IPLSpeakerLayout {
+            type_: ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_STEREO,
+            numSpeakers: 0,
+            speakers: std::ptr::null_mut(),
+        };
+
+        match speaker_layout {
+            SpeakerLayout::Mono => {
+                layout.type_ = ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_MONO;
+            }
+            SpeakerLayout::Stereo => {
+                layout.type_ = ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_STEREO;
+            }
+            SpeakerLayout::Quadraphonic => {
+                layout.type_ = ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_QUADRAPHONIC;
+            }
+            SpeakerLayout::Surround5_1 => {
+                layout.type_ = ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_SURROUND_5_1;
+            }
+            SpeakerLayout::Surround7_1 => {
+                layout.type_ = ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_SURROUND_7_1;
+            }
+            SpeakerLayout::Custom { speakers } => {
+                layout.type_ = ffi::IPLSpeakerLayoutType::IPL_SPEAKERLAYOUTTYPE_CUSTOM;
+                layout.numSpeakers = speakers.len() as i32;
+                // `[f32; 3]` has the same layout as `IPLVector3`.
+                layout.speakers = speakers.as_ptr() as *mut ffi::IPLVector3;
+            }
+        }
+
+        layout
+    }
+}
+
+impl SpeakerLayout {
+    /// Number of channels needed for this layout.
+    pub fn channels(&self) -> u16 {
+        match self {
+            Self::Mono => 1,
+            Self::Stereo => 2,
+            Self::Quadraphonic => 4,
+            Self::Surround5_1 => 6,
+            Self::Surround7_1 => 8,
+            Self::Custom { speakers } => speakers.len() as u16,
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::audio_buffer::Filled;
+use crate::prelude::*;
+
+/// Produces the frames played back by a [`FrameStream`].
+///
+/// Implemented for any `FnMut(&mut DeinterleavedFrame) -> Filled` closure.
+pub trait ProcessFrame {
+    /// Write the next frame into `output`, returning how much of it is valid.
+    fn process_frame(&mut self, output: &mut DeinterleavedFrame) -> Filled;
+}
+
+impl<F> ProcessFrame for F
+where
+    F: FnMut(&mut DeinterleavedFrame) -> Filled,
+{
+    fn process_frame(&mut self, output: &mut DeinterleavedFrame) -> Filled {
+        self(output)
+    }
+}
+
+/// A rodio [`Source`](rodio::Source) that plays back a stream of processed frames.
+///
+/// `process` is called whenever the previous frame has been played and should
+/// write the next frame of output, returning how much of it is valid. The stream
+/// ends after a [`Filled::Empty`] or [`Filled::Partial`] frame.
+///
+/// ```ignore
+/// let mut input = DeinterleavedFrame::new(frame_size, 1, sample_rate);
+/// let stream = FrameStream::new(frame_size, 2, sample_rate, move |output: &mut DeinterleavedFrame| {
+///     let filled = input.push_source(&mut decoder).unwrap();
+///     if filled != Filled::Empty {
+///         binaural_effect.apply_to_buffer(&params, &mut input, output).unwrap();
+///     }
+///     filled
+/// });
+/// sink.append(stream);
+/// ```
+pub struct FrameStream<F> {
+    frame: DeinterleavedFrame,
+    process: F,
+    // Valid samples per channel in the current frame.
+    valid: usize,
+    channel: u16,
+    index: usize,
+    last_frame: bool,
+}
+
+impl<F> FrameStream<F>
+where
+    F: ProcessFrame,
+{
+    pub fn new(frame_size: usize, channels: u16, sample_rate: u32, process: F) -> Self {
+        Self {
+            frame: DeinterleavedFrame::new(frame_size, channels, sample_rate),
+            process,
+            valid: 0,
+            channel: 0,
+            index: 0,
+            last_frame: false,
+        }
+    }
+
+    pub fn processor(&self) -> &F {
+        &self.process
+    }
+
+    pub fn processor_mut(&mut self) -> &mut F {
+        &mut self.process
+    }
+
+    fn next_frame(&mut self) -> bool {
+        if self.last_frame {
+            return false;
+        }
+
+        self.channel = 0;
+        self.index = 0;
+        self.valid = match self.process.process_frame(&mut self.frame) {
+            Filled::Full => self.frame.frame_size(),
+            Filled::Partial(valid) => {
+                self.last_frame = true;
+                valid
+            }
+            Filled::Empty => {
+                self.last_frame = true;
+                0
+            }
+        };
+
+        self.valid > 0
+    }
+}
+
+impl<F> Iterator for FrameStream<F>
+where
+    F: ProcessFrame,
+{
+    type Item = f32;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.frame.channels() == 0 {
+            return None;
+        }
+
+        if self.index >= self.valid && !self.next_frame() {
+            return None;
+        }
+
+        let sample = self.frame.channel(self.channel as usize)[self.index];
+
+        self.channel += 1;
+        if self.channel == self.frame.channels() {
+            self.channel = 0;
+            self.index += 1;
+        }
+
+        Some(sample)
+    }
+}
+
+impl<F> rodio::Source for FrameStream<F>
+where
+    F: ProcessFrame,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        // Channels and sample rate never change mid-stream.
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.frame.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.frame.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Channel `c` holds `start + c * 100 + i` at sample `i`.
+    fn fill(frame: &mut DeinterleavedFrame, start: f32) {
+        for (channel_index, channel) in frame.iter_channels_mut().enumerate() {
+            for (index, sample) in channel.iter_mut().enumerate() {
+                *sample = start + (channel_index * 100 + index) as f32;
+            }
+        }
+    }
+
+    fn channels(frame: &DeinterleavedFrame, valid: usize) -> Vec<Vec<f32>> {
+        frame
+            .iter_channels()
+            .map(|channel| channel[..valid].to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn chains_frames_in_order() {
+        let mut expected = vec![vec![]; 2];
+        let mut frames = 0;
+        let stream = FrameStream::new(4, 2, 48000, |frame: &mut DeinterleavedFrame| {
+            frames += 1;
+            fill(frame, frames as f32 * 1000.0);
+            match frames {
+                1 | 2 => {
+                    crate::extend_deinterleaved(&mut expected, channels(frame, 4));
+                    Filled::Full
+                }
+                _ => {
+                    crate::extend_deinterleaved(&mut expected, channels(frame, 3));
+                    Filled::Partial(3)
+                }
+            }
+        });
+
+        let samples: Vec<f32> = stream.collect();
+        let expected: Vec<f32> = (0..expected[0].len())
+            .flat_map(|index| expected.iter().map(move |channel| channel[index]))
+            .collect();
+        assert_eq!(samples, expected);
+    }
+
+    #[test]
+    fn ends_on_empty() {
+        let mut frames = 0;
+        let mut stream = FrameStream::new(2, 1, 48000, |frame: &mut DeinterleavedFrame| {
+            frames += 1;
+            fill(frame, 0.0);
+            if frames == 1 {
+                Filled::Full
+            } else {
+                Filled::Empty
+            }
+        });
+
+        assert_eq!(stream.by_ref().count(), 2);
+        assert_eq!(stream.next(), None);
+    }
+}